@@ -0,0 +1,33 @@
+//! Renders the scene of `fish/path-trace.fish` with a hand-held camera, which
+//! shakes during the exposure, and prints it as a PPM image.
+//!
+//! ```sh
+//! cargo run --release --example camera_shake > camera_shake.ppm
+//! ```
+
+use path_tracing::prelude::*;
+
+fn main() {
+    let mut scene = Scene::three_spheres();
+    let look_at = scene.camera.look_at;
+
+    // The aim wanders a little around the subject while the shutter is open.
+    let shake = [
+        (0., 0., 0.),
+        (0.05, 0.02, 0.),
+        (-0.03, 0.05, 0.),
+        (0.02, -0.04, 0.),
+    ];
+    let keys = shake
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y, z))| (i as Fl / 3., look_at + Vec3::new(x, y, z)))
+        .collect();
+
+    scene.camera.motion.look_at = Some(Track::new(keys, Interpolation::CatmullRom));
+
+    let camera = Camera::new(scene.camera);
+    let image = camera.render_parallel(&scene.world.into());
+
+    println!("{image}");
+}
@@ -0,0 +1,42 @@
+//! Renders spheres of the same green glass growing from left to right, which
+//! look darker the thicker they are, beside a clear one.
+//!
+//! ```sh
+//! cargo run --release --example colored_glass > colored_glass.ppm
+//! ```
+
+use path_tracing::prelude::*;
+
+fn main() {
+    let mut world = HittableList::default();
+
+    let ground: MaterialObject = LambertianMaterial::new(Color::new(0.8, 0.8, 0.8)).into();
+    world.add(Sphere::new(Pos3::new(0., -100.5, -1.), 100., ground).into());
+
+    // A third of the red and blue light is left after crossing half a unit.
+    let green: MaterialObject = DielectricMaterial::new(1.5)
+        .tinted(Color::new(0.3, 0.8, 0.3), 0.5)
+        .into();
+
+    for (x, radius) in [(-1.6, 0.15), (-1., 0.25), (-0.2, 0.4)] {
+        let center = Pos3::new(x, radius - 0.5, -2.);
+        world.add(Sphere::new(center, radius, green.clone()).into());
+    }
+
+    let clear: MaterialObject = DielectricMaterial::new(1.5).into();
+    world.add(Sphere::new(Pos3::new(1., -0.1, -2.), 0.4, clear).into());
+
+    let camera = Camera::new(CameraOptions {
+        aspect_ratio: 16. / 9.,
+        image_width: 400,
+        samples_per_pixel: 100,
+        look_from: Pos3::new(0., 0.5, 1.),
+        look_at: Pos3::new(0., -0.2, -2.),
+        v_fov: 45.,
+        ..CameraOptions::default()
+    });
+
+    let image = camera.render_parallel(&world.into());
+
+    println!("{image}");
+}
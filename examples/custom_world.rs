@@ -0,0 +1,30 @@
+//! Builds a small world by hand instead of using a built-in scene.
+//!
+//! ```sh
+//! cargo run --release --example custom_world > custom_world.ppm
+//! ```
+
+use path_tracing::prelude::*;
+
+fn main() {
+    let mut world = HittableList::default();
+
+    let ground: MaterialObject = LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into();
+    let glass: MaterialObject = DielectricMaterial::new(1.5).into();
+    let gold: MaterialObject = MetalMaterial::new(Color::new(0.8, 0.6, 0.2), 0.1).into();
+
+    world.add(Sphere::new(Pos3::new(0., -100.5, -1.), 100., ground).into());
+    world.add(Sphere::new(Pos3::new(-0.6, 0., -1.), 0.5, glass).into());
+    world.add(Sphere::new(Pos3::new(0.6, 0., -1.), 0.5, gold).into());
+
+    let camera = Camera::new(CameraOptions {
+        aspect_ratio: 16. / 9.,
+        image_width: 400,
+        samples_per_pixel: 50,
+        ..CameraOptions::default()
+    });
+
+    let image = camera.render_parallel(&world.into());
+
+    println!("{image}");
+}
@@ -0,0 +1,43 @@
+//! Renders the four conductor presets side by side, from a mirror finish on
+//! the left to brushed aluminium on the right.
+//!
+//! ```sh
+//! cargo run --release --example metals > metals.ppm
+//! ```
+
+use path_tracing::prelude::*;
+
+fn main() {
+    let mut world = HittableList::default();
+
+    let ground: MaterialObject = LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into();
+    world.add(Sphere::new(Pos3::new(0., -100.5, -1.), 100., ground).into());
+
+    let metals: [MaterialObject; 4] = [
+        ConductorMaterial::silver(0.).into(),
+        ConductorMaterial::gold(0.1).into(),
+        ConductorMaterial::copper(0.3).into(),
+        ConductorMaterial::aluminium(0.05)
+            .anisotropic(0.05, 0.4)
+            .into(),
+    ];
+
+    for (i, metal) in metals.into_iter().enumerate() {
+        let x = -1.5 + i as Fl;
+        world.add(Sphere::new(Pos3::new(x, 0., -2.), 0.45, metal).into());
+    }
+
+    let camera = Camera::new(CameraOptions {
+        aspect_ratio: 16. / 9.,
+        image_width: 400,
+        samples_per_pixel: 100,
+        look_from: Pos3::new(0., 0.5, 1.),
+        look_at: Pos3::new(0., 0., -2.),
+        v_fov: 50.,
+        ..CameraOptions::default()
+    });
+
+    let image = camera.render_parallel(&world.into());
+
+    println!("{image}");
+}
@@ -0,0 +1,52 @@
+//! Renders dielectrics nested inside each other: a glass ball filled with
+//! tinted water, a ball of ice floating half out of a ball of water, and an
+//! air bubble in glass.
+//!
+//! ```sh
+//! cargo run --release --example nested_dielectrics > nested_dielectrics.ppm
+//! ```
+
+use path_tracing::prelude::*;
+
+fn main() {
+    let mut world = HittableList::default();
+
+    let ground: MaterialObject = LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into();
+    world.add(Sphere::new(Pos3::new(0., -100.5, -1.), 100., ground).into());
+
+    // The water overlaps the glass everywhere but in its thin shell, and has
+    // the higher priority, so it fills the ball.
+    let glass = DielectricMaterial::new(1.5).with_priority(1);
+    let water = DielectricMaterial::new(1.33)
+        .tinted(Color::new(0.4, 0.7, 0.9), 0.5)
+        .with_priority(2);
+    world.add(Sphere::new(Pos3::new(-1.1, 0., -2.), 0.5, glass.into()).into());
+    world.add(Sphere::new(Pos3::new(-1.1, 0., -2.), 0.45, water.into()).into());
+
+    // Ice fills its overlap with the water, through which light bends only a
+    // little.
+    let pond = DielectricMaterial::new(1.33)
+        .tinted(Color::new(0.4, 0.7, 0.9), 0.5)
+        .with_priority(1);
+    let ice = DielectricMaterial::new(1.31).with_priority(2);
+    world.add(Sphere::new(Pos3::new(0., 0., -2.), 0.5, pond.into()).into());
+    world.add(Sphere::new(Pos3::new(0., 0.45, -2.), 0.25, ice.into()).into());
+
+    let air = DielectricMaterial::new(1.).with_priority(2);
+    world.add(Sphere::new(Pos3::new(1.1, 0., -2.), 0.5, glass.into()).into());
+    world.add(Sphere::new(Pos3::new(1.1, 0.1, -2.), 0.25, air.into()).into());
+
+    let camera = Camera::new(CameraOptions {
+        aspect_ratio: 16. / 9.,
+        image_width: 400,
+        samples_per_pixel: 100,
+        look_from: Pos3::new(0., 0.6, 1.),
+        look_at: Pos3::new(0., 0., -2.),
+        v_fov: 50.,
+        ..CameraOptions::default()
+    });
+
+    let image = camera.render_parallel(&world.into());
+
+    println!("{image}");
+}
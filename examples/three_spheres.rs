@@ -0,0 +1,16 @@
+//! Renders the scene of `fish/path-trace.fish` and prints it as a PPM image.
+//!
+//! ```sh
+//! cargo run --release --example three_spheres > three_spheres.ppm
+//! ```
+
+use path_tracing::prelude::*;
+
+fn main() {
+    let scene = Scene::three_spheres();
+    let camera = Camera::new(scene.camera);
+
+    let image = camera.render_parallel(&scene.world.into());
+
+    println!("{image}");
+}
@@ -535,10 +535,18 @@ function ___arr_get -d 'get the ith item of array (one-indexed)'
     ___value $value
 end
 
+function ___random_log -d 'record to $PATH_TRACE_RANDOM_LOG, if set'
+    if set -q PATH_TRACE_RANDOM_LOG
+        string join ' ' -- $argv >>$PATH_TRACE_RANDOM_LOG
+    end
+end
+
 function ___f64_random
     ___arg_null $argv
 
-    ___f64_div (random 0 32768) 32768
+    set -l value (___f64_div (random 0 32768) 32768)
+    ___random_log $value
+    ___value $value
 end
 
 function ___f64_lerp
@@ -1259,6 +1267,8 @@ function ___cam_render_pixel
     ___f64_t y
     ___arg_end
 
+    ___random_log pixel $x $y
+
     set color 0 0 0
 
     for _sample in (seq $self_samples_per_pixel)
@@ -1313,7 +1323,13 @@ function ___cam_render_parallel
             end
 
             echo -n \r"y=$y x=$x spawning" >&2
-            fish $file thread $self $world $x $y >$temp/$y-$x &
+
+            if set -q PATH_TRACE_RANDOM_LOG
+                PATH_TRACE_RANDOM_LOG=$temp/$y-$x.random fish $file thread $self $world $x $y >$temp/$y-$x &
+            else
+                fish $file thread $self $world $x $y >$temp/$y-$x &
+            end
+
             echo -n \r"y=$y x=$x spawned " >&2
         end
     end
@@ -1325,6 +1341,10 @@ function ___cam_render_parallel
     cat $temp/(seq 0 (___f64_sub $self_image_height 1))-(seq 0 (___f64_sub $self_image_width 1))
     ___ppm_end
 
+    if set -q PATH_TRACE_RANDOM_LOG
+        cat $temp/*.random >>$PATH_TRACE_RANDOM_LOG
+    end
+
     rm -r $temp
 end
 
@@ -1377,6 +1397,12 @@ switch "$argv[1]"
     case parallel
         ___cam_render_parallel (___main)
 
+    case scene
+        ___cam_render (___cam_new $argv[2..17]) $argv[18..]
+
+    case scene-parallel
+        ___cam_render_parallel (___cam_new $argv[2..17]) $argv[18..]
+
     case thread
         ___cam_render_thread $argv[2..]
 end
@@ -0,0 +1,283 @@
+//! Running sums of samples, from which an image can be taken at any time.
+
+use std::{
+    io::{self, Read, Write},
+    ops::{Index, IndexMut},
+    path::Path,
+};
+
+use crate::{file, float::Fl, float_image::FloatImage, ppm::PPMImage, vec3::Color};
+
+const MAGIC: &[u8; 8] = b"PTACCUM3";
+/// Most pixels of a checkpoint, a 16384 by 16384 image, which keeps a
+/// corrupt header from asking for all the memory.
+const MAX_PIXELS: usize = 1 << 28;
+
+/// The sum of the samples taken for a pixel.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct AccumulatedColor {
+    pub sum: Color,
+    /// Sum of the squared luminance of each sample, to estimate the noise.
+    pub sum_squares: Fl,
+    pub samples: usize,
+}
+
+impl AccumulatedColor {
+    pub fn add(&mut self, other: &Self) {
+        self.sum += other.sum;
+        self.sum_squares += other.sum_squares;
+        self.samples += other.samples;
+    }
+
+    pub fn add_sample(&mut self, color: Color) {
+        self.sum += color;
+        self.sum_squares += color.luminance() * color.luminance();
+        self.samples += 1;
+    }
+
+    /// The mean of the samples, or black if there are none.
+    pub fn average(&self) -> Color {
+        if self.samples == 0 {
+            Color::default()
+        } else {
+            self.sum / (self.samples as Fl)
+        }
+    }
+
+    /// Estimated standard error of the mean luminance, relative to it.
+    /// Relative errors of dark pixels are measured against `0.01` instead.
+    pub fn relative_error(&self) -> Fl {
+        if self.samples < 2 {
+            return Fl::INFINITY;
+        }
+
+        let n = self.samples as Fl;
+        let mean = self.sum.luminance() / n;
+        let variance = ((self.sum_squares / n - mean * mean) * n / (n - 1.)).max(0.);
+
+        (variance / n).sqrt() / mean.max(0.01)
+    }
+}
+
+/// Per-pixel sums of linear colors and their sample counts.
+///
+/// Unlike a [`PPMImage`], more samples can be added at any time, and two
+/// accumulators of the same scene can be merged. It can be saved to and loaded
+/// from a checkpoint file, so a render can be resumed after it is interrupted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accumulator {
+    width: usize,
+    height: usize,
+    /// Seed of the samples, which a render resuming from a checkpoint must
+    /// use too. `None` for random samples, or samples of several seeds.
+    seed: Option<u64>,
+    pixels: Vec<AccumulatedColor>,
+}
+
+impl Accumulator {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            seed: None,
+            pixels: vec![AccumulatedColor::default(); width * height],
+        }
+    }
+
+    /// The same accumulator, for samples of the given seed.
+    pub fn with_seed(self, seed: Option<u64>) -> Self {
+        Self { seed, ..self }
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    pub const fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn pixels(&self) -> &[AccumulatedColor] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [AccumulatedColor] {
+        &mut self.pixels
+    }
+
+    /// The fewest samples taken for any pixel.
+    pub fn min_samples(&self) -> usize {
+        self.pixels.iter().map(|p| p.samples).min().unwrap_or(0)
+    }
+
+    /// The relative error of each pixel, averaged over the image.
+    pub fn noise(&self) -> Fl {
+        self.pixels.iter().map(|p| p.relative_error()).sum::<Fl>() / (self.pixels.len() as Fl)
+    }
+
+    /// Adds the samples of another accumulator of the same size.
+    ///
+    /// The two must have been rendered with different seeds, otherwise the
+    /// same samples are counted twice, so accumulators of the same seed are
+    /// refused. The merged samples no longer have a seed.
+    pub fn merge(&mut self, other: &Self) -> io::Result<()> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot merge a {}x{} accumulator into a {}x{} one",
+                    other.width, other.height, self.width, self.height
+                ),
+            ));
+        }
+
+        if let (Some(seed), Some(other_seed)) = (self.seed, other.seed) {
+            if seed == other_seed {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("cannot merge two accumulators of the same seed {seed}"),
+                ));
+            }
+        }
+
+        for (pixel, other) in self.pixels.iter_mut().zip(&other.pixels) {
+            pixel.add(other);
+        }
+
+        self.seed = None;
+
+        Ok(())
+    }
+
+    /// Averages the samples of each pixel into an image.
+    pub fn to_image(&self) -> PPMImage {
+        PPMImage::new_with(
+            self.width,
+            self.height,
+            self.pixels.iter().map(|p| p.average().into()).collect(),
+        )
+    }
+
+    /// Averages the samples of each pixel into a three-channel image, which
+    /// keeps values above one.
+    pub fn to_float_image(&self) -> FloatImage {
+        let mut image = FloatImage::new(self.width, self.height, 3);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                image.set_vec3(x, y, self[(x, y)].average());
+            }
+        }
+
+        image
+    }
+
+    /// Writes the accumulator in a little-endian binary format.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.width as u64).to_le_bytes())?;
+        writer.write_all(&(self.height as u64).to_le_bytes())?;
+        writer.write_all(&u64::from(self.seed.is_some()).to_le_bytes())?;
+        writer.write_all(&self.seed.unwrap_or(0).to_le_bytes())?;
+
+        for pixel in &self.pixels {
+            writer.write_all(&pixel.sum.x().to_le_bytes())?;
+            writer.write_all(&pixel.sum.y().to_le_bytes())?;
+            writer.write_all(&pixel.sum.z().to_le_bytes())?;
+            writer.write_all(&pixel.sum_squares.to_le_bytes())?;
+            writer.write_all(&(pixel.samples as u64).to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads an accumulator written by [`Accumulator::write_to`].
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an accumulator checkpoint",
+            ));
+        }
+
+        let mut next = || -> io::Result<[u8; 8]> {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            Ok(bytes)
+        };
+
+        let width = u64::from_le_bytes(next()?) as usize;
+        let height = u64::from_le_bytes(next()?) as usize;
+        let count = width
+            .checked_mul(height)
+            .filter(|&count| count <= MAX_PIXELS)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("a checkpoint of {width}x{height} pixels is too large"),
+                )
+            })?;
+
+        let has_seed = u64::from_le_bytes(next()?) != 0;
+        let seed = u64::from_le_bytes(next()?);
+        let seed = has_seed.then_some(seed);
+
+        // Grown as pixels are read, so a truncated file fails before taking
+        // all of the memory its header asks for.
+        let mut pixels = Vec::with_capacity(count.min(1 << 16));
+
+        for _ in 0..count {
+            let sum = Color::new(
+                Fl::from_le_bytes(next()?),
+                Fl::from_le_bytes(next()?),
+                Fl::from_le_bytes(next()?),
+            );
+            let sum_squares = Fl::from_le_bytes(next()?);
+            let samples = u64::from_le_bytes(next()?) as usize;
+            pixels.push(AccumulatedColor {
+                sum,
+                sum_squares,
+                samples,
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            seed,
+            pixels,
+        })
+    }
+
+    /// Saves a checkpoint. The file is replaced at once, so an interrupted
+    /// save leaves the previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        file::replace(path, |writer| self.write_to(writer))
+    }
+
+    /// Loads a checkpoint written by [`Accumulator::save`].
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::read_from(io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
+
+impl Index<(usize, usize)> for Accumulator {
+    type Output = AccumulatedColor;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        &self.pixels[index.0 + index.1 * self.width]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Accumulator {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+        &mut self.pixels[index.0 + index.1 * self.width]
+    }
+}
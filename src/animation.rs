@@ -0,0 +1,413 @@
+//! Sequences of frames over a timeline, for turntables and short clips.
+//!
+//! An [`Animation`] is a still world, keyframed spheres and a camera whose
+//! options can be keyframed through [`CameraMotion`]. Each frame is rendered
+//! as a [`Scene`] at the time of the frame and saved to its own numbered file,
+//! so that an interrupted render can be resumed by skipping the frames which
+//! are already saved.
+//!
+//! [`CameraMotion`]: crate::camera::CameraMotion
+
+use std::{
+    f64::consts::TAU,
+    io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    camera::{Camera, CameraOptions},
+    conductor::ConductorMaterial,
+    dielectric::DielectricMaterial,
+    float::{lerp, mix_seed, Fl},
+    hittable_list::HittableList,
+    keyframe::{Interpolation, Track},
+    lambertian::LambertianMaterial,
+    material::MaterialObject,
+    metal::MetalMaterial,
+    rough_dielectric::RoughDielectricMaterial,
+    scene::Scene,
+    sphere::Sphere,
+    vec3::{Color, Pos3, Vec3},
+};
+
+/// Keys of the orbit of a turntable, which is close enough to a circle with
+/// a Catmull-Rom spline.
+const TURNTABLE_KEYS: usize = 24;
+
+/// A material whose parameters are keyframed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimatedMaterial {
+    Conductor {
+        eta: Track<Color>,
+        k: Track<Color>,
+        roughness_x: Track<Fl>,
+        roughness_y: Track<Fl>,
+    },
+    Dielectric {
+        refraction_index: Track<Fl>,
+        absorption: Track<Color>,
+        /// See [`DielectricMaterial::with_priority`].
+        priority: Option<u32>,
+    },
+    Lambertian {
+        albedo: Track<Color>,
+    },
+    Metal {
+        albedo: Track<Color>,
+        fuzz: Track<Fl>,
+    },
+    RoughDielectric {
+        refraction_index: Track<Fl>,
+        roughness: Track<Fl>,
+    },
+}
+
+impl AnimatedMaterial {
+    /// The material at `time`.
+    pub fn at(&self, time: Fl) -> MaterialObject {
+        match self {
+            AnimatedMaterial::Conductor {
+                eta,
+                k,
+                roughness_x,
+                roughness_y,
+            } => ConductorMaterial::new(eta.at(time), k.at(time), 0.)
+                .anisotropic(roughness_x.at(time), roughness_y.at(time))
+                .into(),
+            AnimatedMaterial::Dielectric {
+                refraction_index,
+                absorption,
+                priority,
+            } => {
+                let material = DielectricMaterial::new(refraction_index.at(time))
+                    .absorbing(absorption.at(time));
+
+                match priority {
+                    Some(priority) => material.with_priority(*priority).into(),
+                    None => material.into(),
+                }
+            }
+            AnimatedMaterial::Lambertian { albedo } => {
+                LambertianMaterial::new(albedo.at(time)).into()
+            }
+            AnimatedMaterial::Metal { albedo, fuzz } => {
+                MetalMaterial::new(albedo.at(time), fuzz.at(time)).into()
+            }
+            AnimatedMaterial::RoughDielectric {
+                refraction_index,
+                roughness,
+            } => RoughDielectricMaterial::new(refraction_index.at(time), roughness.at(time)).into(),
+        }
+    }
+}
+
+impl From<MaterialObject> for AnimatedMaterial {
+    /// A material which does not change.
+    fn from(value: MaterialObject) -> Self {
+        match value {
+            MaterialObject::Conductor(material) => AnimatedMaterial::Conductor {
+                eta: Track::constant(material.eta()),
+                k: Track::constant(material.k()),
+                roughness_x: Track::constant(material.roughness().0),
+                roughness_y: Track::constant(material.roughness().1),
+            },
+            MaterialObject::Dielectric(material) => AnimatedMaterial::Dielectric {
+                refraction_index: Track::constant(material.refraction_index()),
+                absorption: Track::constant(material.absorption()),
+                priority: material.priority(),
+            },
+            MaterialObject::Lambertian(material) => AnimatedMaterial::Lambertian {
+                albedo: Track::constant(material.albedo()),
+            },
+            MaterialObject::Metal(material) => AnimatedMaterial::Metal {
+                albedo: Track::constant(material.albedo()),
+                fuzz: Track::constant(material.fuzz()),
+            },
+            MaterialObject::RoughDielectric(material) => AnimatedMaterial::RoughDielectric {
+                refraction_index: Track::constant(material.refraction_index()),
+                roughness: Track::constant(material.roughness()),
+            },
+        }
+    }
+}
+
+/// A sphere whose center and material are keyframed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimatedSphere {
+    pub center: Track<Pos3>,
+    pub radius: Fl,
+    pub material: AnimatedMaterial,
+}
+
+impl AnimatedSphere {
+    /// The sphere during an exposure from `open` to `close`. It moves in a
+    /// straight line between its centers at both times, which is enough for
+    /// the motion blur of a single frame.
+    fn exposed(&self, open: Fl, close: Fl) -> Sphere {
+        let material = self.material.at((open + close) / 2.);
+
+        if close > open {
+            Sphere::new_moving_between(
+                (self.center.at(open), open),
+                (self.center.at(close), close),
+                self.radius,
+                material,
+            )
+        } else {
+            Sphere::new(self.center.at(open), self.radius, material)
+        }
+    }
+}
+
+/// Which times of the scene are rendered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timeline {
+    /// Scene time of the first frame.
+    pub start: Fl,
+    /// Frames per unit of scene time.
+    pub frame_rate: Fl,
+    pub frames: usize,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            start: 0.,
+            frame_rate: 24.,
+            frames: 48,
+        }
+    }
+}
+
+impl Timeline {
+    /// Scene time at which `frame` starts.
+    pub fn time(&self, frame: usize) -> Fl {
+        self.start + frame as Fl / self.frame_rate
+    }
+
+    /// Scene time at which the last frame ends.
+    pub fn end(&self) -> Fl {
+        self.time(self.frames)
+    }
+}
+
+/// A world which changes over a timeline, and the camera looking at it.
+///
+/// The shutter of the camera is given in frames rather than in scene time:
+/// the default shutter, from 0 to 1, stays open for the whole of each frame.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    /// Objects which are the same in every frame.
+    pub world: HittableList,
+    pub spheres: Vec<AnimatedSphere>,
+    pub camera: CameraOptions,
+    pub timeline: Timeline,
+}
+
+impl Animation {
+    /// Names accepted by [`Animation::from_name`].
+    pub const NAMES: [&'static str; 2] = ["bouncing-spheres", "turntable"];
+
+    /// Returns the built-in animation with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bouncing-spheres" => Some(Self::bouncing_spheres()),
+            "turntable" => Some(Self::turntable(Scene::three_spheres(), Timeline::default())),
+            _ => None,
+        }
+    }
+
+    /// The camera of `scene` orbiting once around the point it looks at,
+    /// over the timeline. Objects of the scene which move keep moving.
+    pub fn turntable(scene: Scene, timeline: Timeline) -> Self {
+        let mut camera = scene.camera;
+        let offset = camera.look_from - camera.look_at;
+
+        let keys = (0..=TURNTABLE_KEYS)
+            .map(|key| {
+                let fraction = key as Fl / TURNTABLE_KEYS as Fl;
+                let (sin, cos) = (fraction * TAU).sin_cos();
+                let rotated = Vec3::new(
+                    offset.x() * cos + offset.z() * sin,
+                    offset.y(),
+                    offset.z() * cos - offset.x() * sin,
+                );
+
+                (
+                    lerp(fraction, timeline.start, timeline.end()),
+                    camera.look_at + rotated,
+                )
+            })
+            .collect();
+
+        camera.motion.look_from = Some(Track::new(keys, Interpolation::CatmullRom));
+
+        Self {
+            world: scene.world,
+            spheres: Vec::new(),
+            camera,
+            timeline,
+        }
+    }
+
+    /// A ball bouncing between a sphere whose color shifts and a mirror
+    /// which blurs, while the camera moves closer.
+    pub fn bouncing_spheres() -> Self {
+        let timeline = Timeline::default();
+        let end = timeline.end();
+
+        let mut world = HittableList::default();
+        world.add(
+            Sphere::new(
+                Pos3::new(0., -1000., 0.),
+                1000.,
+                LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into(),
+            )
+            .into(),
+        );
+
+        // Two bounces, each a parabola sampled finely enough for straight
+        // lines between keys.
+        let bounce = (0..=48)
+            .map(|key| {
+                let time = end * key as Fl / 48.;
+                let phase = (2. * time / end).fract() * 2. - 1.;
+                let height = 0.5 + 1.5 * (1. - phase * phase);
+
+                (time, Pos3::new(0., height, 0.))
+            })
+            .collect();
+
+        let spheres = vec![
+            AnimatedSphere {
+                center: Track::new(bounce, Interpolation::Linear),
+                radius: 0.5,
+                material: AnimatedMaterial::Dielectric {
+                    refraction_index: Track::constant(1.5),
+                    absorption: Track::constant(Color::new(0., 0., 0.)),
+                    priority: None,
+                },
+            },
+            AnimatedSphere {
+                center: Track::constant(Pos3::new(-1.5, 0.6, -0.5)),
+                radius: 0.6,
+                material: AnimatedMaterial::Lambertian {
+                    albedo: Track::new(
+                        vec![
+                            (0., Color::new(0.8, 0.2, 0.1)),
+                            (end / 2., Color::new(0.1, 0.6, 0.2)),
+                            (end, Color::new(0.1, 0.2, 0.8)),
+                        ],
+                        Interpolation::CatmullRom,
+                    ),
+                },
+            },
+            AnimatedSphere {
+                center: Track::constant(Pos3::new(1.5, 0.6, -0.5)),
+                radius: 0.6,
+                material: AnimatedMaterial::Metal {
+                    albedo: Track::constant(Color::new(0.8, 0.8, 0.8)),
+                    fuzz: Track::new(
+                        vec![(0., 0.), (end / 2., 0.5), (end, 0.)],
+                        Interpolation::CatmullRom,
+                    ),
+                },
+            },
+        ];
+
+        let mut camera = CameraOptions {
+            aspect_ratio: 16. / 9.,
+            image_width: 320,
+            samples_per_pixel: 32,
+            max_depth: 20,
+            v_fov: 40.,
+            look_from: Pos3::new(0., 1.5, 7.),
+            look_at: Pos3::new(0., 1., 0.),
+            ..CameraOptions::default()
+        };
+        camera.motion.look_from = Some(Track::new(
+            vec![(0., camera.look_from), (end, Pos3::new(0., 1.2, 5.))],
+            Interpolation::Linear,
+        ));
+
+        Self {
+            world,
+            spheres,
+            camera,
+            timeline,
+        }
+    }
+
+    /// The scene of `frame`, with the shutter moved to the time of the
+    /// frame and the seed changed for each frame.
+    pub fn scene(&self, frame: usize) -> Scene {
+        let time = self.timeline.time(frame);
+        let duration = 1. / self.timeline.frame_rate;
+
+        let mut camera = self.camera.clone();
+        camera.seed = camera.seed.map(|seed| mix_seed(&[seed, frame as u64]));
+
+        let shutter = &mut camera.shutter;
+        shutter.open = time + shutter.open * duration;
+        shutter.close = time + shutter.close * duration;
+        shutter.rolling_readout *= duration;
+
+        let mut world = self.world.clone();
+
+        for sphere in &self.spheres {
+            world.add(sphere.exposed(shutter.open, shutter.close).into());
+        }
+
+        Scene { world, camera }
+    }
+
+    /// Renders the frames of `frames` within the timeline to numbered PPM
+    /// files starting with `prefix`, skipping frames whose file exists.
+    /// `progress` is told about each frame, with its file, before it is
+    /// rendered or skipped. Returns the paths of the frames which were
+    /// rendered.
+    pub fn render(
+        &self,
+        frames: Range<usize>,
+        prefix: &Path,
+        mut progress: impl FnMut(usize, &Path, FrameStatus),
+    ) -> io::Result<Vec<PathBuf>> {
+        let mut rendered = Vec::new();
+
+        for frame in frames.start..frames.end.min(self.timeline.frames) {
+            let path = frame_path(prefix, frame);
+
+            if path.exists() {
+                progress(frame, &path, FrameStatus::Skipped);
+                continue;
+            }
+
+            progress(frame, &path, FrameStatus::Rendering);
+
+            let scene = self.scene(frame);
+            let image = Camera::new(scene.camera).render_parallel(&scene.world.into());
+
+            image.save(&path)?;
+            rendered.push(path);
+        }
+
+        Ok(rendered)
+    }
+}
+
+/// What [`Animation::render`] does with a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameStatus {
+    Rendering,
+    /// Its file already exists.
+    Skipped,
+}
+
+/// The file of `frame`: `prefix` followed by the frame number on four
+/// digits, as in `frame-0042.ppm`.
+pub fn frame_path(prefix: &Path, frame: usize) -> PathBuf {
+    let mut path = prefix.as_os_str().to_owned();
+    path.push(format!("{frame:04}.ppm"));
+    path.into()
+}
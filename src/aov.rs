@@ -0,0 +1,158 @@
+//! Arbitrary output variables: what the camera saw at the first hit of each
+//! pixel, besides its color.
+
+use std::{io, path::Path};
+
+use crate::{
+    float::Fl,
+    float_image::FloatImage,
+    hittable::{HitRecord, HittableObject},
+    material::{Material, MaterialObject},
+    vec3::{Color, Pos3, Vec3},
+};
+
+/// The first hit of every sample, written by
+/// [`Camera::render_with_aovs`](crate::Camera::render_with_aovs).
+///
+/// Albedo is averaged over every sample, with rays which miss the world
+/// counting as the background. Normal, depth and position are averaged over
+/// the samples which hit something, pixels where none did have a depth of
+/// infinity. IDs are those of the first sample, or -1 for the background.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aovs {
+    /// Color of the surface, three channels.
+    pub albedo: FloatImage,
+    /// Shading normal, facing the ray, three channels.
+    pub normal: FloatImage,
+    /// Distance from the camera along its viewing direction, one channel.
+    pub depth: FloatImage,
+    /// World position, three channels.
+    pub position: FloatImage,
+    /// Index of the material among the distinct materials of the world, one
+    /// channel.
+    pub material_id: FloatImage,
+    /// Index of the sphere, counting the spheres of the world depth-first,
+    /// one channel.
+    pub object_id: FloatImage,
+}
+
+impl Aovs {
+    /// Names of the buffers, as used by [`Aovs::save`].
+    pub const NAMES: [&'static str; 6] = [
+        "albedo",
+        "normal",
+        "depth",
+        "position",
+        "material-id",
+        "object-id",
+    ];
+
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            albedo: FloatImage::new(width, height, 3),
+            normal: FloatImage::new(width, height, 3),
+            depth: FloatImage::new(width, height, 1),
+            position: FloatImage::new(width, height, 3),
+            material_id: FloatImage::new(width, height, 1),
+            object_id: FloatImage::new(width, height, 1),
+        }
+    }
+
+    /// The buffers along with their names, in the order of [`Aovs::NAMES`].
+    pub fn buffers(&self) -> [(&'static str, &FloatImage); 6] {
+        let [albedo, normal, depth, position, material_id, object_id] = Self::NAMES;
+
+        [
+            (albedo, &self.albedo),
+            (normal, &self.normal),
+            (depth, &self.depth),
+            (position, &self.position),
+            (material_id, &self.material_id),
+            (object_id, &self.object_id),
+        ]
+    }
+
+    /// Writes every buffer to a PFM file named `<prefix>.<name>.pfm`.
+    pub fn save(&self, prefix: &Path) -> io::Result<()> {
+        for (name, buffer) in self.buffers() {
+            let mut path = prefix.as_os_str().to_owned();
+            path.push(format!(".{name}.pfm"));
+            buffer.save_pfm(Path::new(&path))?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn set(&mut self, x: usize, y: usize, pixel: &AovPixel, material_ids: &[usize]) {
+        let hits = pixel.hits.max(1) as Fl;
+        let id = |id: Option<usize>| id.map_or(-1., |id| id as Fl);
+
+        self.albedo
+            .set_vec3(x, y, pixel.albedo / pixel.samples.max(1) as Fl);
+        self.normal.set_vec3(x, y, pixel.normal / hits);
+        self.position.set_vec3(x, y, pixel.position / hits);
+        self.depth.get_mut(x, y)[0] = if pixel.hits == 0 {
+            Fl::INFINITY
+        } else {
+            pixel.depth / hits
+        };
+        self.object_id.get_mut(x, y)[0] = id(pixel.object_id);
+        self.material_id.get_mut(x, y)[0] = id(pixel.object_id.map(|id| material_ids[id]));
+    }
+}
+
+/// The sums of the first hits of the samples of a pixel.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct AovPixel {
+    albedo: Color,
+    normal: Vec3,
+    depth: Fl,
+    position: Pos3,
+    hits: usize,
+    samples: usize,
+    object_id: Option<usize>,
+}
+
+impl AovPixel {
+    /// Adds a sample which hit `rec` at `depth` from the camera.
+    pub fn add_hit(&mut self, rec: &HitRecord, depth: Fl) {
+        if self.samples == 0 {
+            self.object_id = Some(rec.object_id);
+        }
+
+        self.albedo += rec.mat.albedo();
+        self.normal += rec.normal;
+        self.depth += depth;
+        self.position += rec.p;
+        self.hits += 1;
+        self.samples += 1;
+    }
+
+    /// Adds a sample which missed the world and saw `background`.
+    pub fn add_miss(&mut self, background: Color) {
+        self.albedo += background;
+        self.samples += 1;
+    }
+}
+
+/// The material ID of every object ID of the world: the index of its
+/// material among the distinct materials, in order of first use.
+pub(crate) fn material_ids(world: &HittableObject) -> Vec<usize> {
+    let mut materials: Vec<&MaterialObject> = Vec::new();
+
+    world
+        .spheres()
+        .into_iter()
+        .map(|sphere| {
+            let material = sphere.material();
+
+            materials
+                .iter()
+                .position(|&other| other == material)
+                .unwrap_or_else(|| {
+                    materials.push(material);
+                    materials.len() - 1
+                })
+        })
+        .collect()
+}
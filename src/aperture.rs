@@ -0,0 +1,166 @@
+//! The shape of the lens opening, which shapes out-of-focus highlights.
+
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{float::Fl, interval::Interval, ppm::PPMImage, vec3::Vec3};
+
+/// The opening of the lens, on which depth of field rays start.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum ApertureShape {
+    /// A perfect disk, giving round bokeh.
+    #[default]
+    Disk,
+    /// A regular polygon of `blades` sides, as formed by the blades of an
+    /// iris diaphragm.
+    Polygon {
+        blades: usize,
+        /// Rotation of the polygon in degrees.
+        rotation: Fl,
+        /// How much the blades bulge outwards, from 0 for straight edges to 1
+        /// for a disk.
+        curvature: Fl,
+    },
+    /// A grayscale image covering the aperture, white where light goes
+    /// through.
+    Mask(Arc<ApertureMask>),
+}
+
+/// How light passes through the lens.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Aperture {
+    pub shape: ApertureShape,
+    /// How much the lens barrel clips the aperture towards the corners of
+    /// the image, from 0 for not at all. At 1 the aperture of the corner
+    /// pixels is clipped by a disk shifted by its radius, turning bokeh into
+    /// cat's eyes and darkening the corners.
+    pub cat_eye: Fl,
+}
+
+impl Aperture {
+    /// A random point on the aperture, in a unit disk, uniform over its area.
+    pub(crate) fn sample(&self) -> Vec3 {
+        match &self.shape {
+            ApertureShape::Disk => Vec3::random_in_unit_disk(),
+            ApertureShape::Polygon {
+                blades,
+                rotation,
+                curvature,
+            } => sample_polygon(*blades, rotation.to_radians(), *curvature),
+            ApertureShape::Mask(mask) => mask.sample(),
+        }
+    }
+
+    /// Whether the lens barrel lets a ray through `point` of the aperture
+    /// reach the pixel at `offset` from the center of the image, where the
+    /// corners are at a distance of one.
+    pub(crate) fn is_unclipped(&self, point: Vec3, offset: (Fl, Fl)) -> bool {
+        let shifted = Vec3::new(
+            point.x() + self.cat_eye * offset.0,
+            point.y() + self.cat_eye * offset.1,
+            0.,
+        );
+
+        self.cat_eye <= 0. || shifted.squared_abs() <= 1.
+    }
+}
+
+/// Samples a polygon inscribed in the unit circle by rejection from the
+/// disk, which is exact for any curvature.
+fn sample_polygon(blades: usize, rotation: Fl, curvature: Fl) -> Vec3 {
+    if blades < 3 {
+        return Vec3::random_in_unit_disk();
+    }
+
+    let half_angle = PI / blades as Fl;
+    let apothem = half_angle.cos();
+    let half_chord = half_angle.sin();
+
+    // Each blade is an arc through two vertices, bulging by `sagitta` past
+    // the straight edge. The polygon is the intersection of the disks of the
+    // arcs, or of half-planes for straight blades.
+    let sagitta = curvature.clamp(0., 1.) * (1. - apothem);
+    let radius = (half_chord * half_chord + sagitta * sagitta) / (2. * sagitta);
+    let center = apothem + sagitta - radius;
+
+    loop {
+        let p = Vec3::random_in_unit_disk();
+
+        let inside = (0..blades).all(|blade| {
+            let angle = rotation + (2 * blade + 1) as Fl * half_angle;
+            let normal = Vec3::new(angle.cos(), angle.sin(), 0.);
+
+            if sagitta <= 0. {
+                p.dot(&normal) <= apothem
+            } else {
+                (p - normal * center).squared_abs() <= radius * radius
+            }
+        });
+
+        if inside {
+            return p;
+        }
+    }
+}
+
+/// A grayscale image of the aperture, mapped onto the square around the unit
+/// disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApertureMask {
+    width: usize,
+    height: usize,
+    /// Sum of the transmission of the pixels up to each one, in row order,
+    /// relative to the total.
+    cdf: Vec<Fl>,
+}
+
+impl ApertureMask {
+    /// Reads the transmission from the brightness of the image. `None` if
+    /// the image is black.
+    pub fn new(image: &PPMImage) -> Option<Self> {
+        let brightness: Vec<Fl> = image
+            .pixels()
+            .iter()
+            .map(|p| (Fl::from(p.r()) + Fl::from(p.g()) + Fl::from(p.b())) / (3. * 255.))
+            .collect();
+
+        let mut cdf: Vec<Fl> = brightness
+            .iter()
+            .scan(0., |sum, b| {
+                *sum += b;
+                Some(*sum)
+            })
+            .collect();
+
+        let total = cdf.last().copied().unwrap_or_default();
+
+        (total > 0.).then(|| {
+            cdf.iter_mut().for_each(|c| *c /= total);
+
+            Self {
+                width: image.width(),
+                height: image.height(),
+                cdf,
+            }
+        })
+    }
+
+    /// A random point of the square, with a density proportional to the
+    /// transmission there. Pixels are picked from their share of the total
+    /// transmission, so even a nearly black mask takes one try.
+    fn sample(&self) -> Vec3 {
+        let unit = Interval::new(0., 1.);
+
+        // Black pixels add nothing to the sum, so no number falls on them.
+        let u = unit.random();
+        let pixel = self
+            .cdf
+            .partition_point(|&c| c <= u)
+            .min(self.cdf.len() - 1);
+        let (x, y) = (pixel % self.width, pixel / self.width);
+
+        let s = (x as Fl + unit.random()) / self.width as Fl;
+        let t = (y as Fl + unit.random()) / self.height as Fl;
+
+        Vec3::new(2. * s - 1., 1. - 2. * t, 0.)
+    }
+}
@@ -0,0 +1,50 @@
+//! Animated PNG files, which keep every color of the frames.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+    time::Duration,
+};
+
+use png::{AdaptiveFilterType, BitDepth, ColorType, Encoder};
+
+use crate::{
+    file,
+    ppm::{self, PPMImage},
+};
+
+/// Writes `frames` as an animated PNG which loops forever, showing each
+/// frame for `delay`. Every frame must have the size of the first one.
+pub fn write_apng(frames: &[PPMImage], delay: Duration, writer: &mut impl Write) -> io::Result<()> {
+    let (width, height) = ppm::frame_size(frames)?;
+
+    let mut encoder = Encoder::new(writer, width, height);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_adaptive_filter(AdaptiveFilterType::Adaptive);
+    // 0 plays to loop forever.
+    encoder.set_animated(frames.len() as u32, 0)?;
+
+    let milliseconds = delay.as_millis().min(u128::from(u16::MAX)) as u16;
+    encoder.set_frame_delay(milliseconds, 1000)?;
+
+    // The first frame is also the still image shown by viewers which do not
+    // support animation.
+    let mut writer = encoder.write_header()?;
+
+    for frame in frames {
+        let data: Vec<u8> = frame
+            .pixels()
+            .iter()
+            .flat_map(|p| [p.r(), p.g(), p.b()])
+            .collect();
+        writer.write_image_data(&data)?;
+    }
+
+    Ok(writer.finish()?)
+}
+
+/// Saves `frames` to an animated PNG file, replacing it at once.
+pub fn save_apng(frames: &[PPMImage], delay: Duration, path: &Path) -> io::Result<()> {
+    file::replace(path, |writer| write_apng(frames, delay, writer))
+}
@@ -1,28 +1,156 @@
-use indicatif::{ParallelProgressIterator, ProgressIterator};
+//! The camera, which turns a world into an image.
+
+use std::{
+    io,
+    ops::{Add, Mul, Sub},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressIterator};
 use rayon::prelude::*;
 
 use crate::{
-    float::{lerp, random, Fl},
-    hittable::{Hittable, HittableObject},
+    accumulator::{AccumulatedColor, Accumulator},
+    aov::{self, AovPixel, Aovs},
+    aperture::Aperture,
+    crop::Crop,
+    distortion::{Distortion, Intrinsics},
+    float::{lerp, mix_seed, random, random_time, with_random_source, Fl, RandomSource},
+    hittable::{HitRecord, Hittable, HittableObject},
     interval::Interval,
+    keyframe::Track,
+    lens::{Lens, LensCamera, LensRay, MILLIMETERS_PER_UNIT},
     material::Material,
     ppm::{PPMColor, PPMImage},
+    progressive::{ProgressiveOptions, StopReason},
+    projection::Projection,
     ray::Ray,
+    shutter::Shutter,
+    stats::{self, RenderStats, Termination},
+    tile::{Tile, TileOptions},
     vec3::{Color, Pos3, Vec3},
 };
 
-#[derive(Debug, Clone, Copy)]
+/// Parameters used to build a [`Camera`].
+#[derive(Debug, Clone)]
 pub struct CameraOptions {
+    /// Ratio of image width over height.
     pub aspect_ratio: Fl,
+    /// Rendered image width in pixels.
     pub image_width: usize,
+    /// Rendered image height in pixels. If `None`, it is the image width over
+    /// the aspect ratio, rounded down. It must be `None` for the projections
+    /// which have their own aspect ratio.
+    pub image_height: Option<usize>,
+    /// Count of random samples for each pixel.
     pub samples_per_pixel: usize,
+    /// Maximum number of ray bounces into scene.
     pub max_depth: usize,
+    /// Vertical view angle (field of view) in degrees.
     pub v_fov: Fl,
+    /// How pixels are mapped to rays.
+    pub projection: Projection,
+    /// A calibrated pinhole camera, replacing `v_fov` and `aspect_ratio` for
+    /// the perspective projection.
+    pub intrinsics: Option<Intrinsics>,
+    /// How the lens bends straight lines, for the perspective projection.
+    pub distortion: Distortion,
+    /// Point camera is looking from.
     pub look_from: Pos3,
+    /// Point camera is looking at.
     pub look_at: Pos3,
+    /// Camera-relative "up" direction.
     pub vup: Vec3,
+    /// Variation angle of rays through each pixel in degrees.
     pub defocus_angle: Fl,
+    /// Distance from `look_from` to the plane of perfect focus.
     pub focus_dist: Fl,
+    /// Shape of the lens opening, scaled to `defocus_angle`.
+    pub aperture: Aperture,
+    /// The lens in front of the film, for the perspective projection.
+    pub lens: Lens,
+    /// When rays are traced, for motion blur.
+    pub shutter: Shutter,
+    /// Keyframes overriding the options above, to move the camera.
+    pub motion: CameraMotion,
+    /// Seed for the random numbers of each pixel, making renders repeatable.
+    /// If `None`, every render is different.
+    pub seed: Option<u64>,
+    /// How the image is split between threads. It does not change the result.
+    pub tiles: TileOptions,
+    /// The part of the image to render, or `None` for all of it.
+    pub crop: Option<Crop>,
+    /// What rays which leave the world see.
+    pub background: Background,
+}
+
+/// Keyframed camera options, in scene time. Options without a track keep
+/// their value from [`CameraOptions`].
+///
+/// Every ray is traced from the camera as it is at the time of the ray, so a
+/// camera moving during the exposure blurs the image, and a camera moving
+/// between frames flies through the scene.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CameraMotion {
+    pub look_from: Option<Track<Pos3>>,
+    pub look_at: Option<Track<Pos3>>,
+    pub vup: Option<Track<Vec3>>,
+    pub v_fov: Option<Track<Fl>>,
+    pub focus_dist: Option<Track<Fl>>,
+}
+
+impl CameraMotion {
+    /// Whether no option is keyframed.
+    pub fn is_still(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The light coming from outside the world.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Background {
+    /// A gradient from white at the horizon to blue overhead.
+    #[default]
+    Sky,
+    /// The same color in every direction, as in a furnace test.
+    Uniform(Color),
+}
+
+impl Background {
+    /// The color seen by a ray which leaves the world.
+    pub fn color(&self, r: &Ray) -> Color {
+        match *self {
+            Background::Sky => {
+                let unit_direction = r.direction().normalize();
+                let a = (unit_direction.y() + 1.) / 2.;
+                lerp(a, Color::new(1., 1., 1.), Color::new(0.5, 0.7, 1.))
+            }
+            Background::Uniform(color) => color,
+        }
+    }
+}
+
+impl CameraOptions {
+    /// Sets `focus_dist` to the distance of what `target` sees in `world`,
+    /// and returns it. Leaves it unchanged and returns `None` if nothing is
+    /// seen there.
+    pub fn auto_focus(&mut self, world: &HittableObject, target: &AutoFocus) -> Option<Fl> {
+        let distance = Camera::new(self.clone()).focus_distance(world, target)?;
+        self.focus_dist = distance;
+
+        Some(distance)
+    }
+}
+
+/// Where auto-focus looks for the distance to focus on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AutoFocus {
+    /// The pixel to focus on, or the center of the image.
+    pub pixel: Option<(usize, usize)>,
+    /// Pixels averaged on each side of the target, so that a patch rather
+    /// than a single ray is probed. Zero for a single ray.
+    pub patch_radius: usize,
 }
 
 impl Default for CameraOptions {
@@ -30,49 +158,216 @@ impl Default for CameraOptions {
         Self {
             aspect_ratio: 1.,
             image_width: 100,
+            image_height: None,
             samples_per_pixel: 10,
             max_depth: 10,
             v_fov: 90.,
+            projection: Projection::default(),
+            intrinsics: None,
+            distortion: Distortion::default(),
             look_from: Pos3::default(),
             look_at: Pos3::new(0., 0., -1.),
             vup: Pos3::new(0., 1., 0.),
             defocus_angle: 0.,
             focus_dist: 10.,
+            aperture: Aperture::default(),
+            lens: Lens::default(),
+            shutter: Shutter::default(),
+            motion: CameraMotion::default(),
+            seed: None,
+            tiles: TileOptions::default(),
+            crop: None,
+            background: Background::default(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Camera {
-    image_width: usize,
-    image_height: usize,
+/// Where the camera is and where its pixels are, at a point in time.
+#[derive(Debug, Default, Clone)]
+struct Frame {
     center: Pos3,
+    /// Directions to the right of the camera, up and from the scene to the
+    /// camera.
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    focus_dist: Fl,
     pixel00_loc: Pos3,
     pixel_delta_u: Vec3,
     pixel_delta_v: Vec3,
-    defocus_angle: Fl,
     defocus_disk_u: Vec3,
     defocus_disk_v: Vec3,
+    /// The pinhole camera matching the viewport, for the perspective
+    /// projection.
+    intrinsics: Option<Intrinsics>,
+}
+
+/// The value of `track` at `time`, or `value` if there is no track.
+fn value_at<T>(track: &Option<Track<T>>, value: T, time: Fl) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Fl, Output = T>,
+{
+    track.as_ref().map_or(value, |track| track.at(time))
+}
+
+/// Samples added to every pixel between two checks of the checkpoint interval.
+const CHECKPOINT_PASS_SAMPLES: usize = 4;
+
+/// A camera, ready to render.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    image_width: usize,
+    image_height: usize,
+    /// The pixels which are rendered.
+    crop: Tile,
+    /// The frame at time 0, used for every ray when the camera is still.
+    frame: Frame,
+    motion: CameraMotion,
+    /// Options of the frame which are not keyframed.
+    look_from: Pos3,
+    look_at: Pos3,
+    vup: Vec3,
+    v_fov: Fl,
+    focus_dist: Fl,
+    projection: Projection,
+    /// The calibrated camera, scaled to the image.
+    intrinsics: Option<Intrinsics>,
+    distortion: Distortion,
+    defocus_angle: Fl,
+    aperture: Aperture,
+    /// The realistic lens, focused on `focus_dist`, if there is one.
+    lens: Option<LensCamera>,
+    shutter: Shutter,
     samples_per_pixel: usize,
     pixel_samples_scale: Fl,
     max_depth: usize,
+    seed: Option<u64>,
+    tiles: TileOptions,
+    background: Background,
 }
 
 impl Camera {
+    /// Computes the viewport and defocus disk from the given options.
+    ///
+    /// Panics if `image_height` is given for a projection whose aspect ratio
+    /// is fixed, such as the equirectangular and cube map ones.
     pub fn new(options: CameraOptions) -> Self {
+        assert!(
+            options.image_height.is_none() || options.projection.aspect_ratio().is_none(),
+            "the {:?} projection sets the image height",
+            options.projection
+        );
+
         let image_width = options.image_width;
-        let image_height = (((image_width as Fl) / options.aspect_ratio).trunc() as usize).max(1);
+        let intrinsics = match options.projection {
+            Projection::Perspective => options.intrinsics,
+            _ => None,
+        };
+        let aspect_ratio = options
+            .projection
+            .aspect_ratio()
+            .or(intrinsics.map(|intrinsics| intrinsics.aspect_ratio()))
+            .unwrap_or(options.aspect_ratio);
+        let image_height = options
+            .image_height
+            .unwrap_or(((image_width as Fl) / aspect_ratio).trunc() as usize)
+            .max(1);
+        let crop = options.crop.map_or(
+            Tile {
+                x: 0,
+                y: 0,
+                width: image_width,
+                height: image_height,
+            },
+            |crop| crop.tile(image_width, image_height),
+        );
+        let intrinsics = intrinsics.map(|intrinsics| intrinsics.scaled(image_width, image_height));
+
+        // Anti-aliasing
+
+        let samples_per_pixel = options.samples_per_pixel;
+        let pixel_samples_scale = 1. / (samples_per_pixel as Fl);
+
+        // Diffuse
+
+        let max_depth = options.max_depth;
+
+        // Lens
+
+        // A realistic lens is focused once, as it is in the middle of the
+        // exposure.
+        let lens = match (&options.lens, options.projection) {
+            (Lens::Realistic(lens), Projection::Perspective) => {
+                let time = (options.shutter.open + options.shutter.close) / 2.;
+                let focus_dist = value_at(&options.motion.focus_dist, options.focus_dist, time);
+
+                Some(LensCamera::new(
+                    lens,
+                    focus_dist,
+                    image_width as Fl / image_height as Fl,
+                ))
+            }
+            _ => None,
+        };
+
+        let mut camera = Self {
+            image_width,
+            image_height,
+            crop,
+            frame: Frame::default(),
+            motion: options.motion,
+            look_from: options.look_from,
+            look_at: options.look_at,
+            vup: options.vup,
+            v_fov: options.v_fov,
+            focus_dist: options.focus_dist,
+            projection: options.projection,
+            intrinsics,
+            distortion: options.distortion,
+            defocus_angle: options.defocus_angle,
+            aperture: options.aperture,
+            lens,
+            shutter: options.shutter,
+            samples_per_pixel,
+            pixel_samples_scale,
+            max_depth,
+            seed: options.seed,
+            tiles: options.tiles,
+            background: options.background,
+        };
+
+        camera.frame = camera.frame_at(0.);
+        camera
+    }
+
+    /// Computes the viewport and defocus disk at `time`, from the keyframed
+    /// options.
+    fn frame_at(&self, time: Fl) -> Frame {
+        let look_from = value_at(&self.motion.look_from, self.look_from, time);
+        let look_at = value_at(&self.motion.look_at, self.look_at, time);
+        let vup = value_at(&self.motion.vup, self.vup, time);
+        let v_fov = value_at(&self.motion.v_fov, self.v_fov, time);
+        let focus_dist = value_at(&self.motion.focus_dist, self.focus_dist, time);
+        let (image_width, image_height) = (self.image_width, self.image_height);
 
         // Camera
 
-        let theta = options.v_fov.to_radians();
-        let h = (theta / 2.).tan();
-        let viewport_height = 2. * h * options.focus_dist;
-        let viewport_width = viewport_height * (image_width as Fl) / (image_height as Fl);
-        let center = options.look_from;
+        // Panoramic projections do not use the viewport.
+        let (viewport_width, viewport_height) = match self.projection {
+            Projection::Orthographic { width, height } => (width, height),
+            _ => {
+                let theta = v_fov.to_radians();
+                let h = (theta / 2.).tan();
+                let viewport_height = 2. * h * focus_dist;
+                let viewport_width = viewport_height * (image_width as Fl) / (image_height as Fl);
+
+                (viewport_width, viewport_height)
+            }
+        };
+        let center = look_from;
 
-        let w = (options.look_from - options.look_at).normalize();
-        let u = options.vup.cross(&w).normalize();
+        let w = (look_from - look_at).normalize();
+        let u = vup.cross(&w).normalize();
         let v = w.cross(&u);
 
         let viewport_u = u * viewport_width;
@@ -81,123 +376,713 @@ impl Camera {
         let pixel_delta_u = viewport_u / (image_width as Fl);
         let pixel_delta_v = viewport_v / (image_height as Fl);
 
-        let viewport_upper_left = center - w * options.focus_dist - (viewport_u + viewport_v) / 2.;
+        let viewport_upper_left = center - w * focus_dist - (viewport_u + viewport_v) / 2.;
 
         let pixel00_loc = viewport_upper_left + (pixel_delta_u + pixel_delta_v) / 2.;
 
-        // Anti-aliasing
-
-        let samples_per_pixel = options.samples_per_pixel;
-        let pixel_samples_scale = 1. / (samples_per_pixel as Fl);
+        // Calibrated camera
 
-        // Diffuse
+        // Pixels are placed by the matrix rather than the field of view.
+        let (pixel00_loc, pixel_delta_u, pixel_delta_v) = match self.intrinsics {
+            Some(intrinsics) => {
+                let pixel_delta_u = u * (focus_dist / intrinsics.fx);
+                let pixel_delta_v = v * -(focus_dist / intrinsics.fy);
+                let pixel00_loc = center
+                    - w * focus_dist
+                    - pixel_delta_u * intrinsics.cx
+                    - pixel_delta_v * intrinsics.cy;
 
-        let max_depth = options.max_depth;
+                (pixel00_loc, pixel_delta_u, pixel_delta_v)
+            }
+            None => (pixel00_loc, pixel_delta_u, pixel_delta_v),
+        };
+        let intrinsics = match self.projection {
+            Projection::Perspective => Some(
+                self.intrinsics
+                    .unwrap_or_else(|| Intrinsics::from_v_fov(v_fov, image_width, image_height)),
+            ),
+            _ => None,
+        };
 
         // Defocus Blur
 
-        let defocus_angle = options.defocus_angle;
-        let defocus_radius = options.focus_dist * (defocus_angle / 2.).to_radians().tan();
+        let defocus_radius = focus_dist * (self.defocus_angle / 2.).to_radians().tan();
 
         let defocus_disk_u = u * defocus_radius;
         let defocus_disk_v = v * defocus_radius;
 
-        Self {
-            image_width,
-            image_height,
+        Frame {
             center,
+            u,
+            v,
+            w,
+            focus_dist,
             pixel00_loc,
             pixel_delta_u,
             pixel_delta_v,
-            defocus_angle,
             defocus_disk_u,
             defocus_disk_v,
-            samples_per_pixel,
-            pixel_samples_scale,
-            max_depth,
+            intrinsics,
         }
     }
 
-    fn get_ray(&self, x: usize, y: usize) -> Ray {
+    pub const fn image_width(&self) -> usize {
+        self.image_width
+    }
+
+    pub const fn image_height(&self) -> usize {
+        self.image_height
+    }
+
+    /// The pixels of the image which are rendered, the whole image unless it
+    /// is cropped. Rendered images have the size of the crop.
+    pub const fn crop(&self) -> Tile {
+        self.crop
+    }
+
+    pub const fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The pixel of the whole image at pixel `(x, y)` of the crop.
+    const fn uncropped(&self, x: usize, y: usize) -> (usize, usize) {
+        (self.crop.x + x, self.crop.y + y)
+    }
+
+    /// The mean distance along the view direction of the first hits of rays
+    /// from the center of the lens through the pixels of `target`, at the
+    /// opening of the shutter. `None` if no ray hits anything, or for
+    /// panoramic projections, which have no depth of field.
+    pub fn focus_distance(&self, world: &HittableObject, target: &AutoFocus) -> Option<Fl> {
+        if self.projection.is_panoramic() {
+            return None;
+        }
+
+        let time = self.shutter.open;
+        let frame = self.frame_at(time);
+
+        // Position of the target in pixels, from the corner of the image.
+        let (x, y) = match target.pixel {
+            Some((x, y)) => (x as Fl + 0.5, y as Fl + 0.5),
+            None => (self.image_width as Fl / 2., self.image_height as Fl / 2.),
+        };
+
+        let radius = target.patch_radius as isize;
+        let offsets = (-radius..=radius).flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)));
+
+        let depths: Vec<Fl> = offsets
+            .filter_map(|(dx, dy)| {
+                let (x, y) = (x + dx as Fl, y + dy as Fl);
+
+                if x < 0. || y < 0. || x > self.image_width as Fl || y > self.image_height as Fl {
+                    return None;
+                }
+
+                if let Some(lens) = &self.lens {
+                    let s = x / self.image_width as Fl;
+                    let t = y / self.image_height as Fl;
+                    let (origin, direction) = Self::lens_to_world(&frame, lens.chief_ray(s, t)?);
+
+                    let ray = Ray::new(origin, direction).at_time(time);
+                    let hit = Self::hit(&ray, world)?;
+
+                    return Some((hit.p - frame.center).dot(&-frame.w));
+                }
+
+                // Pixel 0 is centered on `pixel00_loc`.
+                let (x, y) = self.undistorted_pixel(&frame, (x - 0.5, y - 0.5))?;
+                let point = frame.pixel00_loc + frame.pixel_delta_u * x + frame.pixel_delta_v * y;
+                let origin = match self.projection {
+                    Projection::Orthographic { .. } => point + frame.w * frame.focus_dist,
+                    _ => frame.center,
+                };
+
+                let ray = Ray::new(origin, point - origin).at_time(time);
+                let hit = Self::hit(&ray, world)?;
+
+                Some((hit.p - frame.center).dot(&-frame.w))
+            })
+            .collect();
+
+        (!depths.is_empty()).then(|| depths.iter().sum::<Fl>() / depths.len() as Fl)
+    }
+
+    /// A random ray through the pixel and the weight of its color, or `None`
+    /// if the projection or the lens sends no ray through the sampled point.
+    fn get_ray(&self, x: usize, y: usize) -> Option<(Ray, Fl)> {
+        if self.motion.is_still() {
+            return self.get_ray_in(&self.frame, x, y, None);
+        }
+
+        // A moving camera needs the time first, to know where it is.
+        let time = self.sample_time(y);
+        self.get_ray_in(&self.frame_at(time), x, y, Some(time))
+    }
+
+    /// A random ray through the pixel from `frame`, at `time` or a random
+    /// time.
+    fn get_ray_in(&self, frame: &Frame, x: usize, y: usize, time: Option<Fl>) -> Option<(Ray, Fl)> {
         let offset = Self::sample_square();
-        let pixel_sample = self.pixel00_loc
-            + (self.pixel_delta_u * ((x as Fl) + offset.x()))
-            + (self.pixel_delta_v * ((y as Fl) + offset.y()));
+
+        if let Some(lens) = &self.lens {
+            let s = (x as Fl + 0.5 + offset.x()) / self.image_width as Fl;
+            let t = (y as Fl + 0.5 + offset.y()) / self.image_height as Fl;
+            let time = time.unwrap_or_else(|| self.sample_time(y));
+
+            let (ray, weight) = lens.sample(s, t)?;
+            let (origin, direction) = Self::lens_to_world(frame, ray);
+
+            return Some((
+                Ray::new(origin, direction).at_time(time).for_pixel((x, y)),
+                weight,
+            ));
+        }
+
+        if self.projection.is_panoramic() {
+            let s = (x as Fl + 0.5 + offset.x()) / self.image_width as Fl;
+            let t = (y as Fl + 0.5 + offset.y()) / self.image_height as Fl;
+            let aspect_ratio = self.image_width as Fl / self.image_height as Fl;
+            let d = self.projection.direction(s, t, aspect_ratio);
+
+            // The time is drawn for every sample, to use the same count of
+            // random numbers inside and outside of a fisheye's image circle.
+            let time = time.unwrap_or_else(|| self.sample_time(y));
+
+            return d.map(|d| {
+                let ray = Ray::new(
+                    frame.center,
+                    frame.u * d.x() + frame.v * d.y() + frame.w * d.z(),
+                );
+
+                (ray.at_time(time).for_pixel((x, y)), 1.)
+            });
+        }
+
+        let (px, py) =
+            self.undistorted_pixel(frame, ((x as Fl) + offset.x(), (y as Fl) + offset.y()))?;
+        let pixel_sample =
+            frame.pixel00_loc + (frame.pixel_delta_u * px) + (frame.pixel_delta_v * py);
+
+        // Every ray through the pixel is focused on `pixel_sample`, which is
+        // on the plane of perfect focus.
+        let lens_center = match self.projection {
+            Projection::Orthographic { .. } => pixel_sample + frame.w * frame.focus_dist,
+            _ => frame.center,
+        };
 
         let origin = if self.defocus_angle <= 0. {
-            self.center
+            lens_center
         } else {
-            self.sample_defocus_disk()
+            let p = self.aperture.sample();
+
+            if !self.aperture.is_unclipped(p, self.image_offset(x, y)) {
+                return None;
+            }
+
+            lens_center + frame.defocus_disk_u * p.x() + frame.defocus_disk_v * p.y()
         };
 
-        Ray::new(origin, pixel_sample - origin)
-            .at_time(random())
-            .for_pixel((x, y))
+        Some((
+            Ray::new(origin, pixel_sample - origin)
+                .at_time(time.unwrap_or_else(|| self.sample_time(y)))
+                .for_pixel((x, y)),
+            1.,
+        ))
+    }
+
+    /// The position of the pixel which a pinhole camera would see at pixel
+    /// position `p` of the distorted image, or `None` if the distortion does
+    /// not map it.
+    fn undistorted_pixel(&self, frame: &Frame, p: (Fl, Fl)) -> Option<(Fl, Fl)> {
+        match &frame.intrinsics {
+            Some(intrinsics) if !self.distortion.is_none() => {
+                let undistorted = self.distortion.undistort(intrinsics.normalize(p))?;
+                Some(intrinsics.pixel(undistorted))
+            }
+            _ => Some(p),
+        }
+    }
+
+    /// The origin and direction in the world of a ray leaving the lens. The
+    /// film is at the center of the frame, and the lens looks along `-w`.
+    fn lens_to_world(frame: &Frame, ray: LensRay) -> (Pos3, Vec3) {
+        let to_world = |v: Vec3| frame.u * v.x() + frame.v * v.y() - frame.w * v.z();
+
+        (
+            frame.center + to_world(ray.origin) / MILLIMETERS_PER_UNIT,
+            to_world(ray.direction),
+        )
+    }
+
+    /// Depth of a point seen by `ray` for the depth AOV: along the view
+    /// direction, or the distance from the camera for panoramic projections,
+    /// which also see behind it. A moving camera is measured from where it
+    /// was when it sent the ray.
+    fn depth(&self, ray: &Ray, p: Pos3) -> Fl {
+        let moved;
+        let frame = if self.motion.is_still() {
+            &self.frame
+        } else {
+            moved = self.frame_at(ray.time());
+            &moved
+        };
+
+        if self.projection.is_panoramic() {
+            (p - frame.center).abs()
+        } else {
+            (p - frame.center).dot(&-frame.w)
+        }
     }
 
     fn sample_square() -> Vec3 {
         Vec3::new(random() - 0.5, random() - 0.5, 0.)
     }
 
-    fn sample_defocus_disk(&self) -> Vec3 {
-        let p = Vec3::random_in_unit_disk();
-        self.center + self.defocus_disk_u * p.x() + self.defocus_disk_v * p.y()
+    fn sample_time(&self, y: usize) -> Fl {
+        let row = (y as Fl + 0.5) / self.image_height as Fl;
+        self.shutter.sample_time(random_time(), row)
+    }
+
+    /// Offset of the center of the pixel from the center of the image, up
+    /// and to the right, where the corners are at a distance of one.
+    fn image_offset(&self, x: usize, y: usize) -> (Fl, Fl) {
+        let (width, height) = (self.image_width as Fl, self.image_height as Fl);
+        let diagonal = width.hypot(height);
+
+        (
+            (2. * x as Fl + 1. - width) / diagonal,
+            (height - 2. * y as Fl - 1.) / diagonal,
+        )
     }
 
-    fn ray_color(r: &Ray, depth: usize, world: &HittableObject) -> Color {
+    fn ray_color(&self, r: &Ray, depth: usize, world: &HittableObject) -> Color {
+        // Count of rays in the path so far, including this one.
+        let length = self.max_depth - depth + 1;
+
         if depth == 0 {
+            stats::count_path(length - 1, Termination::MaxDepth);
             return Color::default();
         }
 
-        if let Some(rec) = world.hit(r, Interval::new(0.001, Fl::INFINITY)) {
-            if let Some(rec) = rec.mat.scatter(r, &rec) {
-                return Self::ray_color(&rec.scattered, depth - 1, world) * rec.attenuation;
+        if depth < self.max_depth {
+            stats::count_bounce_ray();
+        }
+
+        let hit = Self::hit(r, world);
+        self.shade(r, hit.as_ref(), depth, world)
+    }
+
+    fn hit(r: &Ray, world: &HittableObject) -> Option<HitRecord> {
+        world.hit(r, Interval::new(0.001, Fl::INFINITY))
+    }
+
+    /// The color seen by a ray at `depth` which hit `hit`, or nothing.
+    fn shade(
+        &self,
+        r: &Ray,
+        hit: Option<&HitRecord>,
+        depth: usize,
+        world: &HittableObject,
+    ) -> Color {
+        let length = self.max_depth - depth + 1;
+
+        if let Some(rec) = hit {
+            if let Some(scatter) = rec.mat.scatter(r, rec) {
+                return self.ray_color(&scatter.scattered, depth - 1, world)
+                    * scatter.attenuation
+                    * Self::transmittance(r, rec);
             }
 
+            stats::count_path(length, Termination::Absorbed);
             return Color::default();
         }
 
-        let unit_direction = r.direction().normalize();
-        let a = (unit_direction.y() + 1.) / 2.;
-        lerp(a, Color::new(1., 1., 1.), Color::new(0.5, 0.7, 1.))
+        stats::count_path(length, Termination::Escaped);
+        self.background.color(r)
     }
 
-    fn render_pixel(&self, world: &HittableObject, x: usize, y: usize) -> PPMColor {
-        let mut color = Color::default();
+    /// Fraction of the light leaving `rec` which reaches the origin of `r`
+    /// through its medium.
+    fn transmittance(r: &Ray, rec: &HitRecord) -> Color {
+        if r.media().is_empty() {
+            return Color::new(1., 1., 1.);
+        }
 
-        for _sample in 0..self.samples_per_pixel {
-            let ray = self.get_ray(x, y);
-            color += Self::ray_color(&ray, self.max_depth, world);
+        let medium = r.medium();
+
+        if medium.is_clear() {
+            return Color::new(1., 1., 1.);
+        }
+
+        medium.transmittance(rec.t * r.direction().abs())
+    }
+
+    /// Runs `sample` with the random numbers of the pixel when there is a
+    /// seed, so the result only depends on the pixel and `first_sample`.
+    fn with_pixel_seed<R>(
+        &self,
+        x: usize,
+        y: usize,
+        first_sample: usize,
+        sample: impl FnOnce() -> R,
+    ) -> R {
+        match self.seed {
+            Some(seed) => {
+                let seed = mix_seed(&[seed, x as u64, y as u64, first_sample as u64]);
+                with_random_source(RandomSource::seeded(seed), sample).0
+            }
+            None => sample(),
         }
+    }
+
+    /// Takes `samples` samples of a pixel of the whole image, which already
+    /// has `first_sample` samples. With a seed, the result only depends on
+    /// these arguments, and not on the crop.
+    pub fn sample_pixel(
+        &self,
+        world: &HittableObject,
+        x: usize,
+        y: usize,
+        first_sample: usize,
+        samples: usize,
+    ) -> AccumulatedColor {
+        self.with_pixel_seed(x, y, first_sample, || {
+            let mut color = AccumulatedColor::default();
+
+            for _sample in 0..samples {
+                stats::count_sample();
+
+                let Some((ray, weight)) = self.get_ray(x, y) else {
+                    color.add_sample(Color::default());
+                    continue;
+                };
+
+                stats::count_camera_ray();
+                color.add_sample(self.ray_color(&ray, self.max_depth, world) * weight);
+            }
+
+            color
+        })
+    }
+
+    /// Like [`Camera::sample_pixel`] from the first sample, also recording the
+    /// first hit of every sample. The color is the same as without AOVs.
+    fn sample_pixel_with_aovs(
+        &self,
+        world: &HittableObject,
+        x: usize,
+        y: usize,
+    ) -> (AccumulatedColor, AovPixel) {
+        self.with_pixel_seed(x, y, 0, || {
+            let mut color = AccumulatedColor::default();
+            let mut aov = AovPixel::default();
 
-        (color * self.pixel_samples_scale).into()
+            for _sample in 0..self.samples_per_pixel {
+                stats::count_sample();
+
+                let Some((ray, weight)) = self.get_ray(x, y) else {
+                    color.add_sample(Color::default());
+                    aov.add_miss(Color::default());
+                    continue;
+                };
+
+                stats::count_camera_ray();
+
+                // The first hit is shared with the path, so the random
+                // numbers are used in the same order.
+                let hit = Self::hit(&ray, world);
+
+                match &hit {
+                    Some(rec) => aov.add_hit(rec, self.depth(&ray, rec.p)),
+                    None => aov.add_miss(self.background.color(&ray)),
+                }
+
+                let sample = if self.max_depth == 0 {
+                    self.ray_color(&ray, 0, world)
+                } else {
+                    self.shade(&ray, hit.as_ref(), self.max_depth, world)
+                };
+                color.add_sample(sample * weight);
+            }
+
+            (color, aov)
+        })
     }
 
+    /// Renders a single pixel of the whole image.
+    pub fn render_pixel(&self, world: &HittableObject, x: usize, y: usize) -> PPMColor {
+        let color = self.sample_pixel(world, x, y, 0, self.samples_per_pixel);
+        (color.sum * self.pixel_samples_scale).into()
+    }
+
+    /// Renders the world one pixel at a time on the current thread.
     pub fn render(&self, world: &HittableObject) -> PPMImage {
-        let mut image = PPMImage::new(self.image_width, self.image_height);
+        self.render_with_stats(world).0
+    }
+
+    /// Renders the world like [`Camera::render`], and returns the statistics
+    /// of the render.
+    pub fn render_with_stats(&self, world: &HittableObject) -> (PPMImage, RenderStats) {
+        let start = Instant::now();
+        let mut image = PPMImage::new(self.crop.width, self.crop.height);
+        stats::take();
 
         for y in (0..image.height()).progress() {
             for x in 0..image.width() {
-                image[(x, y)] = self.render_pixel(world, x, y);
+                let (u, v) = self.uncropped(x, y);
+                image[(x, y)] = self.render_pixel(world, u, v);
             }
         }
 
-        image
+        let mut stats = stats::take();
+        stats.elapsed = start.elapsed();
+        (image, stats)
+    }
+
+    /// Renders every tile with `render`, handing the tiles out in order to
+    /// the threads of the rayon thread pool as they become idle.
+    fn render_tiles<T: Send>(
+        &self,
+        progress: ProgressBar,
+        render: impl Fn(Tile) -> Vec<T> + Sync,
+    ) -> (Vec<(Tile, Vec<T>)>, RenderStats) {
+        let start = Instant::now();
+        let tiles = self.tiles.tiles(self.crop.width, self.crop.height);
+        progress.set_length(tiles.len() as u64);
+
+        let tiles: Vec<_> = tiles
+            .into_iter()
+            .par_bridge()
+            .map(|tile| {
+                stats::take();
+                let colors = render(tile);
+                (tile, colors, stats::take())
+            })
+            .progress_with(progress)
+            .collect();
+
+        let mut stats = RenderStats::default();
+        let tiles = tiles
+            .into_iter()
+            .map(|(tile, colors, tile_stats)| {
+                stats += &tile_stats;
+                (tile, colors)
+            })
+            .collect();
+
+        stats.elapsed = start.elapsed();
+        (tiles, stats)
     }
 
+    /// Renders the world tile by tile, using every thread of the rayon thread
+    /// pool.
     pub fn render_parallel(&self, world: &HittableObject) -> PPMImage {
-        PPMImage::new_with(
-            self.image_width,
-            self.image_height,
-            (0..self.image_height)
-                .into_par_iter()
-                .progress()
-                .flat_map(move |y| {
-                    (0..self.image_width)
-                        .into_par_iter()
-                        .map(move |x| self.render_pixel(world, x, y))
+        self.render_parallel_with_stats(world).0
+    }
+
+    /// Renders the world like [`Camera::render_parallel`], and returns the
+    /// statistics of the render.
+    pub fn render_parallel_with_stats(&self, world: &HittableObject) -> (PPMImage, RenderStats) {
+        let mut image = PPMImage::new(self.crop.width, self.crop.height);
+
+        let (tiles, stats) = self.render_tiles(ProgressBar::new(0), |tile| {
+            tile.pixels()
+                .map(|(x, y)| {
+                    let (u, v) = self.uncropped(x, y);
+                    self.render_pixel(world, u, v)
                 })
-                .collect(),
-        )
+                .collect()
+        });
+
+        for (tile, colors) in tiles {
+            for (pixel, color) in tile.pixels().zip(colors) {
+                image[pixel] = color;
+            }
+        }
+
+        (image, stats)
+    }
+
+    /// Renders the world like [`Camera::render_parallel`], also recording the
+    /// first hit of every pixel into [`Aovs`], and returns the statistics of
+    /// the render.
+    pub fn render_with_aovs(&self, world: &HittableObject) -> (Accumulator, Aovs, RenderStats) {
+        let mut accumulator =
+            Accumulator::new(self.crop.width, self.crop.height).with_seed(self.seed);
+        let mut aovs = Aovs::new(self.crop.width, self.crop.height);
+        let material_ids = aov::material_ids(world);
+
+        let (tiles, stats) = self.render_tiles(ProgressBar::new(0), |tile| {
+            tile.pixels()
+                .map(|(x, y)| {
+                    let (u, v) = self.uncropped(x, y);
+                    self.sample_pixel_with_aovs(world, u, v)
+                })
+                .collect()
+        });
+
+        for (tile, pixels) in tiles {
+            for ((x, y), (color, aov)) in tile.pixels().zip(pixels) {
+                accumulator[(x, y)] = color;
+                aovs.set(x, y, &aov, &material_ids);
+            }
+        }
+
+        (accumulator, aovs, stats)
+    }
+
+    /// Adds `samples` samples to every pixel of the accumulator, in parallel,
+    /// and returns the statistics of this pass.
+    pub fn render_into(
+        &self,
+        world: &HittableObject,
+        accumulator: &mut Accumulator,
+        samples: usize,
+    ) -> RenderStats {
+        assert_eq!(
+            (accumulator.width(), accumulator.height()),
+            (self.crop.width, self.crop.height),
+            "the accumulator does not match the size of the crop",
+        );
+
+        let previous: &Accumulator = accumulator;
+
+        let (tiles, stats) = self.render_tiles(ProgressBar::hidden(), |tile| {
+            tile.pixels()
+                .map(|(x, y)| {
+                    let (u, v) = self.uncropped(x, y);
+                    self.sample_pixel(world, u, v, previous[(x, y)].samples, samples)
+                })
+                .collect()
+        });
+
+        for (tile, colors) in tiles {
+            for (pixel, color) in tile.pixels().zip(colors) {
+                accumulator[pixel].add(&color);
+            }
+        }
+
+        stats
+    }
+
+    /// Renders until every pixel has `samples_per_pixel` samples, resuming
+    /// from the samples already in the accumulator. The accumulator is saved
+    /// to `checkpoint` whenever `interval` has passed, and at the end. Returns
+    /// the statistics of the passes rendered.
+    pub fn render_checkpointed(
+        &self,
+        world: &HittableObject,
+        accumulator: &mut Accumulator,
+        checkpoint: &Path,
+        interval: Duration,
+    ) -> io::Result<RenderStats> {
+        let progress = ProgressBar::new(self.samples_per_pixel as u64);
+        let mut last_save = Instant::now();
+        let mut stats = RenderStats::default();
+
+        loop {
+            let done = accumulator.min_samples();
+            progress.set_position(done.min(self.samples_per_pixel) as u64);
+
+            if done >= self.samples_per_pixel {
+                break;
+            }
+
+            let pass = CHECKPOINT_PASS_SAMPLES.min(self.samples_per_pixel - done);
+            stats += &self.render_into(world, accumulator, pass);
+
+            if last_save.elapsed() >= interval {
+                accumulator.save(checkpoint)?;
+                last_save = Instant::now();
+            }
+        }
+
+        progress.finish();
+        accumulator.save(checkpoint)?;
+
+        Ok(stats)
+    }
+
+    /// Renders passes over the whole image until one of the limits in
+    /// `options` is reached, adding to the samples already in the accumulator.
+    /// Returns why it stopped, and the statistics of the passes rendered.
+    pub fn render_progressive(
+        &self,
+        world: &HittableObject,
+        accumulator: &mut Accumulator,
+        options: &ProgressiveOptions,
+    ) -> io::Result<(StopReason, RenderStats)> {
+        let start = Instant::now();
+        let mut last_write = start;
+        let mut last_pass = Duration::ZERO;
+        let mut noise = accumulator.noise();
+        let mut stats = RenderStats::default();
+        let progress = ProgressBar::new_spinner();
+
+        let write = |accumulator: &Accumulator| -> io::Result<()> {
+            if let Some(output) = &options.output {
+                accumulator.to_image().save(output)?;
+            }
+
+            if let Some(checkpoint) = &options.checkpoint {
+                accumulator.save(checkpoint)?;
+            }
+
+            Ok(())
+        };
+
+        let reason = loop {
+            let samples = accumulator.min_samples();
+
+            if options
+                .target_samples
+                .is_some_and(|target| samples >= target)
+            {
+                break StopReason::TargetSamples;
+            }
+
+            if options
+                .noise_threshold
+                .is_some_and(|threshold| noise <= threshold)
+            {
+                break StopReason::NoiseThreshold;
+            }
+
+            if options
+                .time_budget
+                .is_some_and(|budget| start.elapsed() + last_pass > budget)
+            {
+                break StopReason::TimeBudget;
+            }
+
+            let pass = options
+                .target_samples
+                .map_or(options.pass_samples, |target| {
+                    options.pass_samples.min(target - samples)
+                })
+                .max(1);
+
+            let pass_start = Instant::now();
+            stats += &self.render_into(world, accumulator, pass);
+            last_pass = pass_start.elapsed();
+
+            noise = accumulator.noise();
+            progress.set_message(format!(
+                "{} samples per pixel, noise {noise:.4}, {:.1?} elapsed",
+                accumulator.min_samples(),
+                start.elapsed(),
+            ));
+            progress.tick();
+
+            if last_write.elapsed() >= options.write_interval {
+                write(accumulator)?;
+                last_write = Instant::now();
+            }
+        };
+
+        progress.finish();
+        write(accumulator)?;
+
+        Ok((reason, stats))
     }
 }
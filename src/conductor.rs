@@ -0,0 +1,165 @@
+//! Rough metals, with microfacets and the Fresnel reflectance of conductors.
+
+use crate::{
+    float::{random, Fl},
+    hittable::HitRecord,
+    material::{Material, ScatterRecord},
+    microfacet::{Ggx, TangentFrame},
+    ray::Ray,
+    vec3::Color,
+};
+
+/// A metal whose surface is made of GGX microfacets, which reflect light
+/// with the Fresnel reflectance of a conductor of complex index of
+/// refraction `eta + i k`, given for red, green and blue.
+///
+/// Unlike [`MetalMaterial`](crate::metal::MetalMaterial), it never reflects
+/// more light than it receives, and its highlights stretch at grazing
+/// angles as on real metals. Light which bounces more than once between
+/// microfacets is lost, which darkens rough metals: a white one loses 3% of
+/// the light at a roughness of 0.1, 15% at 0.3 and 60% at 1.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ConductorMaterial {
+    eta: Color,
+    k: Color,
+    /// GGX roughness along the tangent and the bitangent.
+    roughness: (Fl, Fl),
+}
+
+impl ConductorMaterial {
+    /// An isotropic conductor. A roughness of 0 is a mirror, and 1 is very
+    /// rough.
+    pub fn new(eta: Color, k: Color, roughness: Fl) -> Self {
+        Self {
+            eta,
+            k,
+            roughness: (roughness, roughness),
+        }
+    }
+
+    /// The same conductor with different roughnesses along lines of latitude
+    /// of the surface and across them, as brushed or turned metal.
+    pub fn anisotropic(self, roughness_x: Fl, roughness_y: Fl) -> Self {
+        Self {
+            roughness: (roughness_x, roughness_y),
+            ..self
+        }
+    }
+
+    pub fn gold(roughness: Fl) -> Self {
+        Self::new(
+            Color::new(0.143, 0.374, 1.442),
+            Color::new(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+
+    pub fn silver(roughness: Fl) -> Self {
+        Self::new(
+            Color::new(0.155, 0.117, 0.138),
+            Color::new(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: Fl) -> Self {
+        Self::new(
+            Color::new(0.200, 0.924, 1.102),
+            Color::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    pub fn aluminium(roughness: Fl) -> Self {
+        Self::new(
+            Color::new(1.657, 0.880, 0.521),
+            Color::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+
+    pub const fn eta(&self) -> Color {
+        self.eta
+    }
+
+    pub const fn k(&self) -> Color {
+        self.k
+    }
+
+    /// Roughness along the tangent and the bitangent.
+    pub const fn roughness(&self) -> (Fl, Fl) {
+        self.roughness
+    }
+
+    /// Reflectance of each channel for light arriving at an angle of cosine
+    /// `cos_i` to the normal.
+    pub fn fresnel(&self, cos_i: Fl) -> Color {
+        let channel = |eta: Fl, k: Fl| fresnel_conductor(cos_i, eta, k);
+
+        Color::new(
+            channel(self.eta.x(), self.k.x()),
+            channel(self.eta.y(), self.k.y()),
+            channel(self.eta.z(), self.k.z()),
+        )
+    }
+}
+
+/// Fresnel reflectance of unpolarized light on a conductor, averaging the
+/// exact reflectances of both polarizations.
+fn fresnel_conductor(cos_i: Fl, eta: Fl, k: Fl) -> Fl {
+    let cos2 = cos_i.clamp(0., 1.).powi(2);
+    let sin2 = 1. - cos2;
+    let (eta2, k2) = (eta * eta, k * k);
+
+    let t0 = eta2 - k2 - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4. * eta2 * k2).sqrt();
+    let a = ((a2_plus_b2 + t0) / 2.).max(0.).sqrt();
+
+    let t1 = a2_plus_b2 + cos2;
+    let t2 = 2. * cos_i * a;
+    let rs = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+
+    (rs + rp) / 2.
+}
+
+impl Material for ConductorMaterial {
+    /// Reflectance at normal incidence.
+    fn albedo(&self) -> Color {
+        self.fresnel(1.)
+    }
+
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let frame = TangentFrame::new(rec.normal);
+        let wo = frame.to_local(-r_in.direction().normalize());
+
+        if wo.z() <= 0. {
+            return None;
+        }
+
+        let ggx = Ggx::new(self.roughness.0, self.roughness.1);
+        let m = ggx.sample_visible_normal(wo, random(), random());
+        let wi = (-wo).reflect(&m);
+
+        // Reflected into the surface, where the next microfacet would catch
+        // it. The masking below gives such paths no weight anyway.
+        if wi.z() <= 0. {
+            return None;
+        }
+
+        // The density of visible normals cancels all of the reflectance but
+        // the Fresnel term and the shadowing.
+        let attenuation = self.fresnel(wo.dot(&m)) * (ggx.g2(wo, wi) / ggx.g1(wo));
+
+        Some(ScatterRecord {
+            attenuation,
+            scattered: Ray::new(rec.p, frame.to_world(wi))
+                .at_time_of(r_in)
+                .in_media_of(r_in)
+                .for_pixel_of(r_in),
+        })
+    }
+}
@@ -0,0 +1,56 @@
+//! Rendering part of the image, to redo a small area without the rest.
+
+use crate::{float::Fl, tile::Tile};
+
+/// The part of the image to render. Its pixels are traced exactly as in the
+/// whole image, so a cropped render can be pasted back into a full one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Crop {
+    /// A rectangle in pixels of the whole image.
+    Pixels(Tile),
+    /// A rectangle in fractions of the width and height of the image, from
+    /// its top left corner. It is widened to whole pixels.
+    Normalized {
+        left: Fl,
+        top: Fl,
+        right: Fl,
+        bottom: Fl,
+    },
+}
+
+impl Crop {
+    /// The pixels of an image of `width` by `height` pixels inside the crop,
+    /// clamped to the image. At least one pixel is rendered.
+    pub fn tile(&self, width: usize, height: usize) -> Tile {
+        let (x0, y0, x1, y1) = match *self {
+            Crop::Pixels(tile) => (tile.x, tile.y, tile.x + tile.width, tile.y + tile.height),
+            Crop::Normalized {
+                left,
+                top,
+                right,
+                bottom,
+            } => {
+                let edge = |fraction: Fl, size: usize, round: fn(Fl) -> Fl| {
+                    round(fraction.clamp(0., 1.) * size as Fl) as usize
+                };
+
+                (
+                    edge(left, width, Fl::floor),
+                    edge(top, height, Fl::floor),
+                    edge(right, width, Fl::ceil),
+                    edge(bottom, height, Fl::ceil),
+                )
+            }
+        };
+
+        let x = x0.min(width.saturating_sub(1));
+        let y = y0.min(height.saturating_sub(1));
+
+        Tile {
+            x,
+            y,
+            width: x1.clamp(x + 1, width.max(x + 1)) - x,
+            height: y1.clamp(y + 1, height.max(y + 1)) - y,
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! An edge-avoiding à-trous wavelet filter, which smooths the noise of a
+//! render without blurring across the edges found in its [`Aovs`].
+//!
+//! Each iteration blurs the image with a 5x5 B3-spline kernel whose taps are
+//! spread twice as far apart as in the previous one, so a few iterations cover
+//! a large area cheaply. Every tap is weighted down when its color, albedo,
+//! normal or depth differ from the center's. The color is divided by the
+//! albedo first, so that textures are kept and only the lighting is filtered.
+//!
+//! Based on Dammertz et al., "Edge-Avoiding À-Trous Wavelet Transform for
+//! fast Global Illumination Filtering" (2010).
+
+use rayon::prelude::*;
+
+use crate::{aov::Aovs, float::Fl, float_image::FloatImage, vec3::Vec3};
+
+/// Settings of [`denoise`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DenoiseOptions {
+    /// Count of filter passes. Each pass doubles the filter radius, from 2
+    /// pixels for the first one, so more passes remove coarser noise at the
+    /// cost of time.
+    pub iterations: usize,
+    /// How different two colors may be and still be blended. Zero leaves the
+    /// image as it is, higher values smooth more and blur more detail.
+    pub strength: Fl,
+}
+
+impl Default for DenoiseOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 5,
+            strength: 1.,
+        }
+    }
+}
+
+/// B3-spline weights of the taps, from the center outwards.
+const KERNEL: [Fl; 5] = [1. / 16., 1. / 4., 3. / 8., 1. / 4., 1. / 16.];
+
+/// Color difference at which a tap is weighted by `1/e` in the first pass,
+/// at strength 1. Each later pass halves it.
+const COLOR_SIGMA: Fl = 0.5;
+/// Albedo difference at which a tap is weighted by `1/e`.
+const ALBEDO_SIGMA: Fl = 0.1;
+/// Exponent applied to the cosine between normals.
+const NORMAL_POWER: i32 = 32;
+/// Relative depth difference per pixel of distance at which a tap is weighted
+/// by `1/e`.
+const DEPTH_SIGMA: Fl = 0.02;
+
+/// Lowest albedo the color is divided by, so black surfaces keep their noise
+/// finite.
+const MIN_ALBEDO: Fl = 0.01;
+
+/// Filters `beauty`, a three-channel image such as
+/// [`Accumulator::to_float_image`](crate::Accumulator::to_float_image),
+/// guided by the first-hit buffers rendered along with it.
+///
+/// # Panics
+///
+/// If `beauty` does not have three channels, or differs in size from the
+/// buffers of `aovs`.
+pub fn denoise(beauty: &FloatImage, aovs: &Aovs, options: &DenoiseOptions) -> FloatImage {
+    assert_eq!(
+        (beauty.width(), beauty.height(), beauty.channels()),
+        (aovs.albedo.width(), aovs.albedo.height(), 3),
+        "the image does not match the AOVs",
+    );
+
+    if options.strength <= 0. || options.iterations == 0 {
+        return beauty.clone();
+    }
+
+    let (width, height) = (beauty.width(), beauty.height());
+    let albedo = |x, y| {
+        let albedo = aovs.albedo.get_vec3(x, y);
+        Vec3::new(
+            albedo.x().max(MIN_ALBEDO),
+            albedo.y().max(MIN_ALBEDO),
+            albedo.z().max(MIN_ALBEDO),
+        )
+    };
+
+    let mut irradiance = FloatImage::new(width, height, 3);
+
+    for y in 0..height {
+        for x in 0..width {
+            irradiance.set_vec3(x, y, beauty.get_vec3(x, y) / albedo(x, y));
+        }
+    }
+
+    for iteration in 0..options.iterations {
+        let step = 1_usize << iteration;
+
+        // Past the size of the image, every tap but the center falls outside
+        // it and the passes change nothing. Stopping there also keeps the
+        // step from overflowing.
+        if step >= width.max(height) {
+            break;
+        }
+
+        let color_sigma = options.strength * COLOR_SIGMA / step as Fl;
+        let previous = &irradiance;
+        let mut filtered = FloatImage::new(width, height, 3);
+
+        filtered
+            .data_mut()
+            .par_chunks_mut(width * 3)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, pixel) in row.chunks_mut(3).enumerate() {
+                    let value = filter_pixel(previous, aovs, x, y, step, color_sigma);
+                    pixel.copy_from_slice(&[value.x(), value.y(), value.z()]);
+                }
+            });
+
+        irradiance = filtered;
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            irradiance.set_vec3(x, y, irradiance.get_vec3(x, y) * albedo(x, y));
+        }
+    }
+
+    irradiance
+}
+
+/// One pass of the filter over a single pixel.
+fn filter_pixel(
+    image: &FloatImage,
+    aovs: &Aovs,
+    x: usize,
+    y: usize,
+    step: usize,
+    color_sigma: Fl,
+) -> Vec3 {
+    let color = image.get_vec3(x, y);
+    let albedo = aovs.albedo.get_vec3(x, y);
+    let normal = aovs.normal.get_vec3(x, y);
+    let depth = aovs.depth.get(x, y)[0];
+
+    let mut sum = Vec3::default();
+    let mut total_weight = 0.;
+
+    for (j, ky) in KERNEL.iter().enumerate() {
+        let Some(qy) = (y + j * step).checked_sub(2 * step) else {
+            continue;
+        };
+
+        if qy >= image.height() {
+            continue;
+        }
+
+        for (i, kx) in KERNEL.iter().enumerate() {
+            let Some(qx) = (x + i * step).checked_sub(2 * step) else {
+                continue;
+            };
+
+            if qx >= image.width() {
+                continue;
+            }
+
+            let q_color = image.get_vec3(qx, qy);
+            let q_depth = aovs.depth.get(qx, qy)[0];
+
+            let color_weight = -(color - q_color).squared_abs() / (color_sigma * color_sigma);
+            let albedo_weight = -(albedo - aovs.albedo.get_vec3(qx, qy)).squared_abs()
+                / (ALBEDO_SIGMA * ALBEDO_SIGMA);
+            let (normal_weight, depth_weight) = if depth.is_infinite() || q_depth.is_infinite() {
+                // Only the sky is infinitely far, and it is only like itself.
+                if depth == q_depth {
+                    (1., 0.)
+                } else {
+                    (0., Fl::NEG_INFINITY)
+                }
+            } else {
+                let normal_weight = normal
+                    .dot(&aovs.normal.get_vec3(qx, qy))
+                    .max(0.)
+                    .powi(NORMAL_POWER);
+                let distance = (x.abs_diff(qx) + y.abs_diff(qy)) as Fl;
+                let depth_weight = -(depth - q_depth).abs()
+                    / (DEPTH_SIGMA * depth.max(Fl::EPSILON) * distance.max(1.));
+
+                (normal_weight, depth_weight)
+            };
+
+            let weight = if (x, y) == (qx, qy) {
+                ky * kx
+            } else {
+                ky * kx * (color_weight + albedo_weight + depth_weight).exp() * normal_weight
+            };
+
+            sum += q_color * weight;
+            total_weight += weight;
+        }
+    }
+
+    sum / total_weight
+}
@@ -1,19 +1,94 @@
+//! Transparent surfaces, such as glass and water.
+
 use crate::{
     float::{random, Fl},
     hittable::HitRecord,
     material::{Material, ScatterRecord},
+    medium::Medium,
     ray::Ray,
     vec3::Color,
 };
 
+/// A dielectric which either reflects or refracts using Schlick's
+/// approximation. It is clear unless it absorbs light inside, which tints
+/// it more where it is thicker.
+///
+/// Without a priority, its refractive index is relative to whatever
+/// surrounds it, so an air bubble in glass is a dielectric of index
+/// `1. / 1.5`, as in the fish renderer. With one, its index is absolute and
+/// light bends by the ratio of the indices of the media on both sides, such
+/// as ice floating in water. Where objects overlap, the one of highest
+/// priority fills the overlap, and surfaces of the others are ignored there.
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct DielectricMaterial {
     refraction_index: Fl,
+    /// Fraction of each channel absorbed per unit of distance inside.
+    absorption: Color,
+    priority: Option<u32>,
 }
 
 impl DielectricMaterial {
+    /// A dielectric with the given refractive index relative to the
+    /// surrounding medium.
     pub fn new(refraction_index: Fl) -> Self {
-        Self { refraction_index }
+        Self {
+            refraction_index,
+            absorption: Color::new(0., 0., 0.),
+            priority: None,
+        }
+    }
+
+    /// The same dielectric, nested among others by `priority`: its index is
+    /// then absolute, and it fills its overlaps with dielectrics of lower
+    /// priorities. Dielectrics without a priority have the lowest one.
+    ///
+    /// A ray is inside at most four objects at once: past that, the objects
+    /// it enters are ignored, as if they were not there.
+    pub fn with_priority(self, priority: u32) -> Self {
+        Self {
+            priority: Some(priority),
+            ..self
+        }
+    }
+
+    /// The same dielectric, absorbing `absorption` of each channel per unit
+    /// of distance travelled inside.
+    pub fn absorbing(self, absorption: Color) -> Self {
+        Self { absorption, ..self }
+    }
+
+    /// The same dielectric, tinted so that white light becomes `color` after
+    /// travelling `distance` inside, which is easier to pick than an
+    /// absorption coefficient.
+    pub fn tinted(self, color: Color, distance: Fl) -> Self {
+        let channel = |c: Fl| -c.clamp(Fl::MIN_POSITIVE, 1.).ln() / distance;
+
+        self.absorbing(Color::new(
+            channel(color.x()),
+            channel(color.y()),
+            channel(color.z()),
+        ))
+    }
+
+    pub const fn refraction_index(&self) -> Fl {
+        self.refraction_index
+    }
+
+    pub const fn absorption(&self) -> Color {
+        self.absorption
+    }
+
+    pub const fn priority(&self) -> Option<u32> {
+        self.priority
+    }
+
+    /// What rays travel through inside.
+    pub fn medium(&self) -> Medium {
+        Medium::new(
+            self.refraction_index,
+            self.absorption,
+            self.priority.unwrap_or(0),
+        )
     }
 
     fn reflectance(cosine: Fl, refraction_index: Fl) -> Fl {
@@ -25,27 +100,41 @@ impl DielectricMaterial {
 
 impl Material for DielectricMaterial {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
-        let ri = if rec.front_face {
-            1. / self.refraction_index
-        } else {
-            self.refraction_index
+        let medium = self.medium();
+        let around = r_in.media().around(rec.object_id);
+        let crossed = r_in.media().crossed(rec.object_id, medium, rec.front_face);
+
+        // Inside an object of higher priority, this surface is not there.
+        if around.priority() > medium.priority() {
+            return Some(ScatterRecord {
+                attenuation: Color::new(1., 1., 1.),
+                scattered: Ray::new(rec.p, *r_in.direction())
+                    .at_time_of(r_in)
+                    .in_media(crossed)
+                    .for_pixel_of(r_in),
+            });
+        }
+
+        let ri = match (self.priority, rec.front_face) {
+            (None, true) => 1. / self.refraction_index,
+            (None, false) => self.refraction_index,
+            (Some(_), true) => around.refraction_index() / self.refraction_index,
+            (Some(_), false) => self.refraction_index / around.refraction_index(),
         };
 
         let unit_direction = r_in.direction().normalize();
         let cos_theta = (-unit_direction).dot(&rec.normal).min(1.);
         let sin_theta = (1. - cos_theta * cos_theta).sqrt();
 
-        let direction = if ri * sin_theta > 1. || Self::reflectance(cos_theta, ri) > random() {
-            unit_direction.reflect(&rec.normal)
+        let scattered = if ri * sin_theta > 1. || Self::reflectance(cos_theta, ri) > random() {
+            Ray::new(rec.p, unit_direction.reflect(&rec.normal)).in_media_of(r_in)
         } else {
-            unit_direction.refract(&rec.normal, ri)
+            Ray::new(rec.p, unit_direction.refract(&rec.normal, ri)).in_media(crossed)
         };
 
         Some(ScatterRecord {
             attenuation: Color::new(1., 1., 1.),
-            scattered: Ray::new(rec.p, direction)
-                .at_time_of(r_in)
-                .for_pixel_of(r_in),
+            scattered: scattered.at_time_of(r_in).for_pixel_of(r_in),
         })
     }
 }
@@ -0,0 +1,176 @@
+//! Distortion of real camera lenses, and calibrated camera intrinsics, to
+//! render images which line up with photographed plates.
+//!
+//! Both follow the conventions of camera calibration tools such as OpenCV:
+//! pixel centers are at integer coordinates from the top left corner, and
+//! normalized coordinates are `x` to the right and `y` down, at a distance
+//! of one along the view direction.
+
+use crate::float::Fl;
+
+/// Most iterations of the inversion of a distortion model, which takes a
+/// handful for the distortion of real lenses.
+const INVERSION_STEPS: usize = 32;
+
+/// A pinhole camera matrix from a calibration, in pixels of an image of
+/// `width` by `height` pixels. It replaces the field of view and aspect
+/// ratio of the camera, and is scaled to the rendered image width.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intrinsics {
+    /// Focal lengths, in pixel widths and heights.
+    pub fx: Fl,
+    pub fy: Fl,
+    /// Principal point, where the view direction meets the image.
+    pub cx: Fl,
+    pub cy: Fl,
+    /// Size of the calibrated image.
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Intrinsics {
+    /// The intrinsics of an image of `width` by `height` pixels with a
+    /// vertical field of view of `v_fov` degrees, square pixels and the
+    /// principal point in the middle.
+    pub fn from_v_fov(v_fov: Fl, width: usize, height: usize) -> Self {
+        let f = height as Fl / 2. / (v_fov.to_radians() / 2.).tan();
+
+        Self {
+            fx: f,
+            fy: f,
+            cx: (width as Fl - 1.) / 2.,
+            cy: (height as Fl - 1.) / 2.,
+            width,
+            height,
+        }
+    }
+
+    /// Ratio of image width over height.
+    pub fn aspect_ratio(&self) -> Fl {
+        self.width as Fl / self.height as Fl
+    }
+
+    /// The same camera for an image of `width` by `height` pixels.
+    pub fn scaled(&self, width: usize, height: usize) -> Self {
+        let sx = width as Fl / self.width as Fl;
+        let sy = height as Fl / self.height as Fl;
+
+        // Pixel edges, not centers, line up.
+        Self {
+            fx: self.fx * sx,
+            fy: self.fy * sy,
+            cx: (self.cx + 0.5) * sx - 0.5,
+            cy: (self.cy + 0.5) * sy - 0.5,
+            width,
+            height,
+        }
+    }
+
+    /// Normalized coordinates of a pixel position.
+    pub fn normalize(&self, (px, py): (Fl, Fl)) -> (Fl, Fl) {
+        ((px - self.cx) / self.fx, (py - self.cy) / self.fy)
+    }
+
+    /// Pixel position of normalized coordinates.
+    pub fn pixel(&self, (x, y): (Fl, Fl)) -> (Fl, Fl) {
+        (x * self.fx + self.cx, y * self.fy + self.cy)
+    }
+}
+
+/// How a lens bends straight lines, in normalized coordinates.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Distortion {
+    /// A perfect pinhole, where straight lines stay straight.
+    #[default]
+    None,
+    /// Radial terms `k1` to `k3` of a polynomial in the squared radius, and
+    /// tangential terms `p1` and `p2` of a lens tilted against the sensor.
+    /// Negative `k1` is barrel distortion and positive `k1` is pincushion.
+    /// Distorted coordinates are computed from undistorted ones.
+    BrownConrady {
+        k1: Fl,
+        k2: Fl,
+        k3: Fl,
+        p1: Fl,
+        p2: Fl,
+    },
+    /// Fitzgibbon's division model, common for wide-angle lenses, where
+    /// undistorted coordinates are the distorted ones divided by
+    /// `1 + k1 r² + k2 r⁴`, with `r` the distorted radius. Negative `k1` is
+    /// barrel distortion.
+    Division { k1: Fl, k2: Fl },
+}
+
+impl Distortion {
+    pub fn is_none(&self) -> bool {
+        *self == Distortion::None
+    }
+
+    /// Where the lens shows a point seen at `(x, y)` by a pinhole, or `None`
+    /// if it does not show it.
+    pub fn distort(&self, (x, y): (Fl, Fl)) -> Option<(Fl, Fl)> {
+        match *self {
+            Distortion::None => Some((x, y)),
+            Distortion::BrownConrady { k1, k2, k3, p1, p2 } => {
+                let r2 = x * x + y * y;
+                let radial = 1. + r2 * (k1 + r2 * (k2 + r2 * k3));
+
+                Some((
+                    x * radial + 2. * p1 * x * y + p2 * (r2 + 2. * x * x),
+                    y * radial + p1 * (r2 + 2. * y * y) + 2. * p2 * x * y,
+                ))
+            }
+            Distortion::Division { .. } => invert((x, y), |p| self.undistort(p)),
+        }
+    }
+
+    /// Where a pinhole shows a point seen at `(x, y)` through the lens, or
+    /// `None` if it is outside of what the model describes.
+    pub fn undistort(&self, (x, y): (Fl, Fl)) -> Option<(Fl, Fl)> {
+        match *self {
+            Distortion::None => Some((x, y)),
+            Distortion::BrownConrady { .. } => invert((x, y), |p| self.distort(p)),
+            Distortion::Division { k1, k2 } => {
+                let r2 = x * x + y * y;
+                let denominator = 1. + r2 * (k1 + r2 * k2);
+
+                (denominator > 0.).then(|| (x / denominator, y / denominator))
+            }
+        }
+    }
+}
+
+/// The point which `f` maps to `target`, found with Newton's method from
+/// `target` itself. `None` if it does not converge.
+fn invert(target: (Fl, Fl), f: impl Fn((Fl, Fl)) -> Option<(Fl, Fl)>) -> Option<(Fl, Fl)> {
+    const H: Fl = 1e-7;
+    let tolerance = 1e-10 * target.0.hypot(target.1).max(1.);
+    let mut p = target;
+
+    for _step in 0..INVERSION_STEPS {
+        let image = f(p)?;
+        let error = (image.0 - target.0, image.1 - target.1);
+
+        if error.0.hypot(error.1) <= tolerance {
+            return Some(p);
+        }
+
+        // The Jacobian of `f` by finite differences.
+        let dx = f((p.0 + H, p.1))?;
+        let dy = f((p.0, p.1 + H))?;
+        let (a, b) = ((dx.0 - image.0) / H, (dy.0 - image.0) / H);
+        let (c, d) = ((dx.1 - image.1) / H, (dy.1 - image.1) / H);
+        let determinant = a * d - b * c;
+
+        if determinant.abs() < Fl::EPSILON {
+            return None;
+        }
+
+        p = (
+            p.0 - (d * error.0 - b * error.1) / determinant,
+            p.1 - (a * error.1 - c * error.0) / determinant,
+        );
+    }
+
+    None
+}
@@ -0,0 +1,24 @@
+//! Helpers for writing output files.
+
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Writes to a temporary file next to `path`, then renames it over `path`.
+/// Readers see either the old or the new file, never a partial one.
+pub(crate) fn replace(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<fs::File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+
+    let mut writer = BufWriter::new(fs::File::create(&temp)?);
+    write(&mut writer)?;
+    writer.flush()?;
+    writer.into_inner()?.sync_all()?;
+
+    fs::rename(&temp, path)
+}
@@ -0,0 +1,446 @@
+//! Interoperability with the reference renderer in `fish/path-trace.fish`.
+//!
+//! Both renderers share the same camera, material and sphere math, so given
+//! the same random numbers they should produce the same image. The fish
+//! renderer records every random number it draws when `PATH_TRACE_RANDOM_LOG`
+//! is set, which [`RandomLog`] reads back so that [`render_recorded`] can
+//! replay them. [`compare`] then checks the two images pixel by pixel.
+//!
+//! Scenes are exchanged using the argument encoding of the fish renderer: the
+//! sixteen numbers of `___cam_opt_t` followed by a hittable such as
+//! `list 1 sphere 0 0 -1 0.5 lambertian 0.1 0.2 0.5`.
+
+use std::{collections::HashMap, error::Error, fmt::Display, str::FromStr};
+
+use crate::{
+    aperture::Aperture,
+    camera::{Background, Camera, CameraOptions},
+    dielectric::DielectricMaterial,
+    float::{with_random_source, Fl, RandomSource, RecordedRandom},
+    hittable::HittableObject,
+    hittable_list::HittableList,
+    lambertian::LambertianMaterial,
+    lens::Lens,
+    material::MaterialObject,
+    metal::MetalMaterial,
+    ppm::PPMImage,
+    projection::Projection,
+    scene::Scene,
+    sphere::Sphere,
+    vec3::{Color, Vec3},
+};
+
+/// Why a scene or a recording could not be exchanged with the fish renderer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FishError {
+    /// The input ended in the middle of a value.
+    UnexpectedEnd,
+    /// The input continues after the end of the value.
+    TrailingInput(String),
+    /// A number could not be parsed.
+    InvalidNumber(String),
+    /// An unknown hittable type.
+    UnknownHittable(String),
+    /// An unknown material type.
+    UnknownMaterial(String),
+    /// Random numbers were recorded before any `pixel x y` line.
+    MissingPixel,
+    /// The scene uses a feature which the other renderer does not have.
+    Unsupported(&'static str),
+    /// The two images do not have the same size.
+    SizeMismatch((usize, usize), (usize, usize)),
+}
+
+impl Display for FishError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FishError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            FishError::TrailingInput(token) => write!(f, "unexpected {token:?} after the end"),
+            FishError::InvalidNumber(token) => write!(f, "invalid number {token:?}"),
+            FishError::UnknownHittable(token) => write!(f, "unknown hittable type {token:?}"),
+            FishError::UnknownMaterial(token) => write!(f, "unknown material type {token:?}"),
+            FishError::MissingPixel => write!(f, "random numbers recorded outside of a pixel"),
+            FishError::Unsupported(feature) => write!(f, "{feature} are not supported"),
+            FishError::SizeMismatch(a, b) => {
+                write!(f, "image sizes differ: {}x{} and {}x{}", a.0, a.1, b.0, b.1)
+            }
+        }
+    }
+}
+
+impl Error for FishError {}
+
+/// Encodes the camera options as the arguments of `___cam_new`.
+pub fn encode_camera_options(options: &CameraOptions) -> String {
+    let v = |v: Vec3| format!("{} {} {}", v.x(), v.y(), v.z());
+
+    format!(
+        "{} {} {} {} {} {} {} {} {} {}",
+        options.aspect_ratio,
+        options.image_width,
+        options.samples_per_pixel,
+        options.max_depth,
+        options.v_fov,
+        v(options.look_from),
+        v(options.look_at),
+        v(options.vup),
+        options.defocus_angle,
+        options.focus_dist,
+    )
+}
+
+fn encode_material(material: &MaterialObject) -> Result<String, FishError> {
+    Ok(match material {
+        MaterialObject::Conductor(_) => return Err(FishError::Unsupported("conductors")),
+        MaterialObject::Dielectric(i) => {
+            if i.absorption() != Color::default() {
+                return Err(FishError::Unsupported("absorbing dielectrics"));
+            }
+
+            if i.priority().is_some() {
+                return Err(FishError::Unsupported("nested dielectrics"));
+            }
+
+            format!("dielectric {}", i.refraction_index())
+        }
+        MaterialObject::Lambertian(i) => {
+            let a = i.albedo();
+            format!("lambertian {} {} {}", a.x(), a.y(), a.z())
+        }
+        MaterialObject::Metal(i) => {
+            let a = i.albedo();
+            format!("metal {} {} {} {}", a.x(), a.y(), a.z(), i.fuzz())
+        }
+        MaterialObject::RoughDielectric(_) => {
+            return Err(FishError::Unsupported("rough dielectrics"))
+        }
+    })
+}
+
+fn encode_spheres(object: &HittableObject, spheres: &mut Vec<String>) -> Result<(), FishError> {
+    match object {
+        HittableObject::Sphere(i) => {
+            if i.is_moving() {
+                return Err(FishError::Unsupported("moving spheres"));
+            }
+
+            let c = i.center(0.);
+            spheres.push(format!(
+                "sphere {} {} {} {} {}",
+                c.x(),
+                c.y(),
+                c.z(),
+                i.radius(),
+                encode_material(i.material())?,
+            ));
+        }
+        HittableObject::List(i) => {
+            for object in i.objects() {
+                encode_spheres(object, spheres)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes the world as a flat `list`, one sphere per line.
+pub fn encode_world(world: &HittableList) -> Result<String, FishError> {
+    let mut spheres = Vec::new();
+
+    for object in world.objects() {
+        encode_spheres(object, &mut spheres)?;
+    }
+
+    let mut result = format!("list {}", spheres.len());
+
+    for sphere in spheres {
+        result.push('\n');
+        result.push_str(&sphere);
+    }
+
+    Ok(result)
+}
+
+/// Encodes the camera options on the first line, followed by the world.
+pub fn encode_scene(scene: &Scene) -> Result<String, FishError> {
+    if scene.camera.background != Background::Sky {
+        return Err(FishError::Unsupported("backgrounds other than the sky"));
+    }
+
+    if scene.camera.projection != Projection::Perspective {
+        return Err(FishError::Unsupported("projections other than perspective"));
+    }
+
+    if scene.camera.aperture != Aperture::default() {
+        return Err(FishError::Unsupported("apertures other than a disk"));
+    }
+
+    if !scene.camera.motion.is_still() {
+        return Err(FishError::Unsupported("moving cameras"));
+    }
+
+    if scene.camera.lens != Lens::default() {
+        return Err(FishError::Unsupported("realistic lenses"));
+    }
+
+    if scene.camera.intrinsics.is_some() || !scene.camera.distortion.is_none() {
+        return Err(FishError::Unsupported("calibrated cameras"));
+    }
+
+    if scene.camera.image_height.is_some() {
+        return Err(FishError::Unsupported("explicit image heights"));
+    }
+
+    if scene.camera.crop.is_some() {
+        return Err(FishError::Unsupported("cropped renders"));
+    }
+
+    Ok(format!(
+        "{}\n{}\n",
+        encode_camera_options(&scene.camera),
+        encode_world(&scene.world)?
+    ))
+}
+
+struct Tokens<'a>(std::str::SplitWhitespace<'a>);
+
+impl<'a> Tokens<'a> {
+    fn new(s: &'a str) -> Self {
+        Self(s.split_whitespace())
+    }
+
+    fn word(&mut self) -> Result<&'a str, FishError> {
+        self.0.next().ok_or(FishError::UnexpectedEnd)
+    }
+
+    fn number<T: FromStr>(&mut self) -> Result<T, FishError> {
+        let token = self.word()?;
+        token
+            .parse()
+            .map_err(|_| FishError::InvalidNumber(token.to_owned()))
+    }
+
+    fn vec(&mut self) -> Result<Vec3, FishError> {
+        Ok(Vec3::new(self.number()?, self.number()?, self.number()?))
+    }
+
+    fn end(&mut self) -> Result<(), FishError> {
+        match self.0.next() {
+            Some(token) => Err(FishError::TrailingInput(token.to_owned())),
+            None => Ok(()),
+        }
+    }
+
+    fn camera_options(&mut self) -> Result<CameraOptions, FishError> {
+        Ok(CameraOptions {
+            aspect_ratio: self.number()?,
+            image_width: self.number()?,
+            samples_per_pixel: self.number()?,
+            max_depth: self.number()?,
+            v_fov: self.number()?,
+            look_from: self.vec()?,
+            look_at: self.vec()?,
+            vup: self.vec()?,
+            defocus_angle: self.number()?,
+            focus_dist: self.number()?,
+            ..CameraOptions::default()
+        })
+    }
+
+    fn material(&mut self) -> Result<MaterialObject, FishError> {
+        match self.word()? {
+            "lambertian" => Ok(LambertianMaterial::new(self.vec()?).into()),
+            "metal" => Ok(MetalMaterial::new(self.vec()?, self.number()?).into()),
+            "dielectric" => Ok(DielectricMaterial::new(self.number()?).into()),
+            "none" => Err(FishError::Unsupported("materials of type none")),
+            token => Err(FishError::UnknownMaterial(token.to_owned())),
+        }
+    }
+
+    fn hittable(&mut self) -> Result<HittableObject, FishError> {
+        match self.word()? {
+            "sphere" => Ok(Sphere::new(self.vec()?, self.number()?, self.material()?).into()),
+            "list" => {
+                let mut list = HittableList::default();
+
+                for _ in 0..self.number::<usize>()? {
+                    list.add(self.hittable()?);
+                }
+
+                Ok(list.into())
+            }
+            "none" => Err(FishError::Unsupported("hittables of type none")),
+            token => Err(FishError::UnknownHittable(token.to_owned())),
+        }
+    }
+}
+
+/// Decodes the output of [`encode_scene`], or any camera options followed by
+/// a hittable in the encoding of the fish renderer.
+pub fn decode_scene(s: &str) -> Result<Scene, FishError> {
+    let mut tokens = Tokens::new(s);
+    let camera = tokens.camera_options()?;
+
+    let world = match tokens.hittable()? {
+        HittableObject::List(list) => list,
+        object => HittableList::new(object),
+    };
+
+    tokens.end()?;
+
+    Ok(Scene { world, camera })
+}
+
+/// Random numbers recorded by the fish renderer, grouped by pixel.
+///
+/// Each pixel starts with a `pixel x y` line, followed by the numbers drawn
+/// while rendering it, one per line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RandomLog {
+    pixels: HashMap<(usize, usize), Vec<Fl>>,
+}
+
+impl RandomLog {
+    /// Returns the numbers recorded for the given pixel.
+    pub fn pixel(&self, x: usize, y: usize) -> &[Fl] {
+        self.pixels.get(&(x, y)).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl FromStr for RandomLog {
+    type Err = FishError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut pixels: HashMap<(usize, usize), Vec<Fl>> = HashMap::new();
+        let mut current = None;
+
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let mut tokens = Tokens::new(line);
+
+            if line.trim_start().starts_with("pixel") {
+                tokens.word()?;
+                let pixel = (tokens.number()?, tokens.number()?);
+                tokens.end()?;
+
+                pixels.insert(pixel, Vec::new());
+                current = Some(pixel);
+            } else {
+                let value = tokens.number()?;
+                tokens.end()?;
+
+                let pixel = current.ok_or(FishError::MissingPixel)?;
+                pixels.entry(pixel).or_default().push(value);
+            }
+        }
+
+        Ok(Self { pixels })
+    }
+}
+
+/// An image rendered from recorded random numbers.
+#[derive(Debug, Clone)]
+pub struct RecordedRender {
+    pub image: PPMImage,
+    /// Pixels which did not use exactly the numbers recorded for them. Their
+    /// paths diverged from the fish renderer, so they are expected to differ.
+    pub desynced: Vec<(usize, usize)>,
+}
+
+/// Renders every pixel on the current thread, replaying the numbers recorded
+/// for it.
+pub fn render_recorded(camera: &Camera, world: &HittableObject, log: &RandomLog) -> RecordedRender {
+    let mut image = PPMImage::new(camera.image_width(), camera.image_height());
+    let mut desynced = Vec::new();
+
+    for y in 0..image.height() {
+        for x in 0..image.width() {
+            let source = RandomSource::Recorded(RecordedRandom::new(log.pixel(x, y).to_vec()));
+            let (color, source) = with_random_source(source, || camera.render_pixel(world, x, y));
+
+            if let RandomSource::Recorded(recorded) = source {
+                if !recorded.is_consumed_exactly() {
+                    desynced.push((x, y));
+                }
+            }
+
+            image[(x, y)] = color;
+        }
+    }
+
+    RecordedRender { image, desynced }
+}
+
+/// The outcome of comparing two images pixel by pixel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParityReport {
+    pub pixels: usize,
+    pub tolerance: u8,
+    /// Pixels with a channel differing by more than the tolerance.
+    pub mismatched: Vec<(usize, usize)>,
+    /// The largest difference of any channel.
+    pub max_difference: u8,
+}
+
+impl ParityReport {
+    pub fn is_match(&self) -> bool {
+        self.mismatched.is_empty()
+    }
+}
+
+impl Display for ParityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} pixels differ by more than {} (largest difference {})",
+            self.mismatched.len(),
+            self.pixels,
+            self.tolerance,
+            self.max_difference,
+        )
+    }
+}
+
+/// How much a channel may differ between the two renderers before
+/// [`compare`] reports the pixel.
+pub const DEFAULT_TOLERANCE: u8 = 2;
+
+/// Compares the two images, allowing each channel to differ by `tolerance`.
+///
+/// The fish renderer rounds every intermediate result to six decimals, so the
+/// images are rarely identical.
+pub fn compare(a: &PPMImage, b: &PPMImage, tolerance: u8) -> Result<ParityReport, FishError> {
+    if (a.width(), a.height()) != (b.width(), b.height()) {
+        return Err(FishError::SizeMismatch(
+            (a.width(), a.height()),
+            (b.width(), b.height()),
+        ));
+    }
+
+    let mut mismatched = Vec::new();
+    let mut max_difference = 0;
+
+    for y in 0..a.height() {
+        for x in 0..a.width() {
+            let (p, q) = (a[(x, y)], b[(x, y)]);
+            let difference = p
+                .r()
+                .abs_diff(q.r())
+                .max(p.g().abs_diff(q.g()))
+                .max(p.b().abs_diff(q.b()));
+
+            max_difference = max_difference.max(difference);
+
+            if difference > tolerance {
+                mismatched.push((x, y));
+            }
+        }
+    }
+
+    Ok(ParityReport {
+        pixels: a.width() * a.height(),
+        tolerance,
+        mismatched,
+        max_difference,
+    })
+}
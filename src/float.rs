@@ -1,11 +1,129 @@
-use std::ops::{Add, Mul};
+//! The floating-point type used throughout the renderer.
 
+use std::{
+    cell::RefCell,
+    ops::{Add, Mul},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Floating-point type used for every computation.
 pub type Fl = f64;
 
+/// Where [`random`] takes its numbers from on the current thread.
+#[derive(Debug, Default, Clone)]
+pub enum RandomSource {
+    /// The thread-local generator of `rand`.
+    #[default]
+    Thread,
+    /// A recorded stream of numbers, replayed in order. It holds no times of
+    /// rays, see [`random_time`].
+    Recorded(RecordedRandom),
+    /// A generator with a fixed seed, giving the same numbers every time.
+    Seeded(Box<StdRng>),
+}
+
+impl RandomSource {
+    pub fn seeded(seed: u64) -> Self {
+        Self::Seeded(Box::new(StdRng::seed_from_u64(seed)))
+    }
+}
+
+/// A recorded stream of random numbers.
+///
+/// Once the stream is exhausted it keeps yielding `0.5`, and counts how many
+/// numbers it had to make up.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RecordedRandom {
+    values: Vec<Fl>,
+    position: usize,
+    overrun: usize,
+}
+
+impl RecordedRandom {
+    pub fn new(values: Vec<Fl>) -> Self {
+        Self {
+            values,
+            position: 0,
+            overrun: 0,
+        }
+    }
+
+    fn next(&mut self) -> Fl {
+        if let Some(&value) = self.values.get(self.position) {
+            self.position += 1;
+            value
+        } else {
+            self.overrun += 1;
+            0.5
+        }
+    }
+
+    /// Count of recorded numbers which were not used.
+    pub fn remaining(&self) -> usize {
+        self.values.len() - self.position
+    }
+
+    /// Count of numbers requested after the stream was exhausted.
+    pub const fn overrun(&self) -> usize {
+        self.overrun
+    }
+
+    /// Whether every recorded number was used, and no more.
+    pub fn is_consumed_exactly(&self) -> bool {
+        self.remaining() == 0 && self.overrun == 0
+    }
+}
+
+thread_local! {
+    static RANDOM_SOURCE: RefCell<RandomSource> = RefCell::default();
+}
+
+/// Returns a random number in `[0, 1)`.
 pub fn random() -> Fl {
-    rand::random()
+    RANDOM_SOURCE.with_borrow_mut(|source| match source {
+        RandomSource::Thread => rand::random(),
+        RandomSource::Recorded(recorded) => recorded.next(),
+        RandomSource::Seeded(rng) => rng.gen(),
+    })
+}
+
+/// Returns a random number in `[0, 1)` for the time of a ray.
+///
+/// Recorded streams come from the fish renderer, which has no time, so they
+/// give 0 without using up a number.
+pub fn random_time() -> Fl {
+    let recorded = RANDOM_SOURCE.with_borrow(|source| matches!(source, RandomSource::Recorded(_)));
+
+    if recorded {
+        0.
+    } else {
+        random()
+    }
+}
+
+/// Runs `f` with [`random`] drawing from `source` on the current thread, then
+/// returns the result along with the source as `f` left it.
+pub fn with_random_source<R>(source: RandomSource, f: impl FnOnce() -> R) -> (R, RandomSource) {
+    let previous = RANDOM_SOURCE.replace(source);
+    let result = f();
+    let source = RANDOM_SOURCE.replace(previous);
+    (result, source)
+}
+
+/// Mixes the values into a single seed, so that nearby values give unrelated
+/// seeds.
+pub fn mix_seed(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e37_79b9_7f4a_7c15, |state, &value| {
+        // SplitMix64
+        let mut z = (state ^ value).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
 }
 
+/// Linearly interpolates from `a` (at `t = 0`) to `b` (at `t = 1`).
 pub fn lerp<T1, T2, T3>(t: Fl, a: T1, b: T1) -> T3
 where
     T1: Mul<Fl, Output = T2>,
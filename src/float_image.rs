@@ -0,0 +1,128 @@
+//! Images of floating point values, for data which does not fit in 8 bits.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{file, float::Fl, ppm::PPMImage, vec3::Vec3};
+
+/// A row-major image with `channels` values per pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatImage {
+    width: usize,
+    height: usize,
+    channels: usize,
+    data: Vec<Fl>,
+}
+
+impl FloatImage {
+    /// An image filled with zeros.
+    pub fn new(width: usize, height: usize, channels: usize) -> Self {
+        Self {
+            width,
+            height,
+            channels,
+            data: vec![0.; width * height * channels],
+        }
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    pub const fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The values of every pixel, row by row.
+    pub fn data(&self) -> &[Fl] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [Fl] {
+        &mut self.data
+    }
+
+    /// Clamps and gamma-corrects the first three channels into an 8-bit
+    /// image.
+    pub fn to_image(&self) -> PPMImage {
+        let pixels = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| self.get_vec3(x, y).into()))
+            .collect();
+
+        PPMImage::new_with(self.width, self.height, pixels)
+    }
+
+    /// The values of a pixel.
+    pub fn get(&self, x: usize, y: usize) -> &[Fl] {
+        let start = (x + y * self.width) * self.channels;
+        &self.data[start..start + self.channels]
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> &mut [Fl] {
+        let start = (x + y * self.width) * self.channels;
+        &mut self.data[start..start + self.channels]
+    }
+
+    /// The first three values of a pixel, as a vector.
+    pub fn get_vec3(&self, x: usize, y: usize) -> Vec3 {
+        let pixel = self.get(x, y);
+        Vec3::new(pixel[0], pixel[1], pixel[2])
+    }
+
+    pub fn set_vec3(&mut self, x: usize, y: usize, value: Vec3) {
+        self.get_mut(x, y)[..3].copy_from_slice(&[value.x(), value.y(), value.z()]);
+    }
+
+    /// Writes the image as a PFM file, which holds one or three channels of
+    /// 32-bit floats. Other channel counts are an error.
+    pub fn save_pfm(&self, path: &Path) -> io::Result<()> {
+        let header = match self.channels {
+            1 => "Pf",
+            3 => "PF",
+            channels => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("PFM files cannot hold {channels} channels"),
+                ))
+            }
+        };
+
+        file::replace(path, |writer| {
+            // A negative scale means little-endian values.
+            write!(writer, "{header}\n{} {}\n-1.0\n", self.width, self.height)?;
+
+            // Rows are stored from the bottom of the image up.
+            for row in self.data.chunks(self.width * self.channels).rev() {
+                for &value in row {
+                    writer.write_all(&(value as f32).to_le_bytes())?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl From<&PPMImage> for FloatImage {
+    /// Maps the 8-bit values of the image to `0..=1`, without undoing its
+    /// gamma.
+    fn from(image: &PPMImage) -> Self {
+        let mut float = FloatImage::new(image.width(), image.height(), 3);
+
+        for (values, pixel) in float.data_mut().chunks_mut(3).zip(image.pixels()) {
+            values.copy_from_slice(&[
+                Fl::from(pixel.r()) / 255.,
+                Fl::from(pixel.g()) / 255.,
+                Fl::from(pixel.b()) / 255.,
+            ]);
+        }
+
+        float
+    }
+}
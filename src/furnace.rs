@@ -0,0 +1,183 @@
+//! White furnace tests, which check that materials neither create nor lose
+//! energy.
+//!
+//! A single sphere sits in a uniform white environment. A material which
+//! reflects or transmits all the light it receives, such as a lambertian
+//! surface of albedo one or any dielectric, then sees white in every
+//! direction and vanishes into the background. Any difference from white is
+//! energy the material lost, or made up.
+
+use std::fmt::Display;
+
+use rayon::prelude::*;
+
+use crate::{
+    camera::{Background, Camera, CameraOptions},
+    conductor::ConductorMaterial,
+    dielectric::DielectricMaterial,
+    float::Fl,
+    hittable::HittableObject,
+    hittable_list::HittableList,
+    lambertian::LambertianMaterial,
+    material::MaterialObject,
+    metal::MetalMaterial,
+    rough_dielectric::RoughDielectricMaterial,
+    scene::Scene,
+    sphere::Sphere,
+    vec3::{Color, Pos3, Vec3},
+};
+
+/// Distance from the camera to the center of the unit sphere.
+const DISTANCE: Fl = 4.;
+/// Vertical view angle in degrees, a little wider than the sphere.
+const V_FOV: Fl = 32.;
+/// Only pixels whose center is inside this fraction of the radius are
+/// measured, so none of them is partly background.
+const MASK_RADIUS: Fl = 0.98;
+
+/// The unit sphere made of `material`, in front of a camera, in a white
+/// furnace.
+pub fn scene(material: MaterialObject, samples_per_pixel: usize) -> Scene {
+    let world = Sphere::new(Pos3::default(), 1., material);
+
+    Scene {
+        world: HittableList::new(world.into()),
+        camera: CameraOptions {
+            aspect_ratio: 1.,
+            image_width: 48,
+            samples_per_pixel,
+            max_depth: 50,
+            v_fov: V_FOV,
+            look_from: Pos3::new(0., 0., DISTANCE),
+            look_at: Pos3::default(),
+            vup: Vec3::new(0., 1., 0.),
+            defocus_angle: 0.,
+            focus_dist: DISTANCE,
+            seed: Some(0),
+            background: Background::Uniform(Color::new(1., 1., 1.)),
+            ..CameraOptions::default()
+        },
+    }
+}
+
+/// Whether the center of the pixel looks at the inner part of the sphere of
+/// [`scene`].
+fn is_masked(x: usize, y: usize, size: usize) -> bool {
+    let h = (V_FOV.to_radians() / 2.).tan();
+    let to_viewport = |i: usize| (2. * (i as Fl + 0.5) / size as Fl - 1.) * h;
+
+    // Distance between the center of the sphere and the ray through the
+    // center of the pixel.
+    let direction = Vec3::new(to_viewport(x), -to_viewport(y), -1.).normalize();
+    let to_center = Pos3::default() - Pos3::new(0., 0., DISTANCE);
+    let distance = (to_center - direction * to_center.dot(&direction)).abs();
+
+    distance < MASK_RADIUS
+}
+
+/// The energy seen on a sphere of `material` in the furnace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FurnaceResult {
+    pub material: MaterialObject,
+    /// Mean luminance over the sphere, relative to the background. One for a
+    /// material which conserves energy.
+    pub energy: Fl,
+}
+
+impl FurnaceResult {
+    /// Fraction of the energy lost, negative if energy is gained.
+    pub fn loss(&self) -> Fl {
+        1. - self.energy
+    }
+}
+
+impl Display for FurnaceResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match &self.material {
+            MaterialObject::Conductor(i) => {
+                let (x, y) = i.roughness();
+                format!("conductor, roughness {x:.2}/{y:.2}")
+            }
+            MaterialObject::Dielectric(i) => format!("dielectric, IOR {:.3}", i.refraction_index()),
+            MaterialObject::Lambertian(i) => {
+                format!("lambertian, albedo {:.2}", i.albedo().luminance())
+            }
+            MaterialObject::Metal(i) => format!("metal, fuzz {:.2}", i.fuzz()),
+            MaterialObject::RoughDielectric(i) => format!(
+                "rough dielectric, IOR {:.3}, roughness {:.2}",
+                i.refraction_index(),
+                i.roughness()
+            ),
+        };
+
+        write!(
+            f,
+            "{name:<28} energy {:.4}, loss {:+.2}%",
+            self.energy,
+            100. * self.loss()
+        )
+    }
+}
+
+/// Renders a sphere of `material` in the furnace with `samples_per_pixel`,
+/// and measures its energy.
+pub fn measure(material: MaterialObject, samples_per_pixel: usize) -> FurnaceResult {
+    let scene = scene(material.clone(), samples_per_pixel);
+    let camera = Camera::new(scene.camera);
+    let world: HittableObject = scene.world.into();
+    let size = camera.image_width();
+
+    let pixels: Vec<(usize, usize)> = (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .filter(|&(x, y)| is_masked(x, y, size))
+        .collect();
+
+    let energy: Fl = pixels
+        .par_iter()
+        .map(|&(x, y)| {
+            let color = camera.sample_pixel(&world, x, y, 0, samples_per_pixel);
+            color.average().luminance()
+        })
+        .sum();
+
+    FurnaceResult {
+        material,
+        energy: energy / pixels.len() as Fl,
+    }
+}
+
+/// A conductor which reflects all the light reaching its microfacets, as its
+/// extinction coefficient is very high.
+pub fn white_conductor(roughness: Fl) -> ConductorMaterial {
+    let white = Color::new(1., 1., 1.);
+    ConductorMaterial::new(white, white * 1e3, roughness)
+}
+
+/// Materials which reflect all the light they receive, across their
+/// parameters: a lambertian of albedo one, white metals of several fuzz
+/// values and dielectrics of several indices of refraction. White
+/// conductors and rough dielectrics follow, which only lose the light
+/// bouncing between their microfacets.
+pub fn materials() -> Vec<MaterialObject> {
+    let white = Color::new(1., 1., 1.);
+
+    let mut materials = vec![LambertianMaterial::new(white).into()];
+    materials.extend([0., 0.1, 0.25, 0.5, 1.].map(|fuzz| MetalMaterial::new(white, fuzz).into()));
+    materials.extend(
+        [1., 1. / 1.5, 1.33, 1.5, 2., 2.4].map(|index| DielectricMaterial::new(index).into()),
+    );
+    materials.extend([0., 0.1, 0.3, 0.6].map(|roughness| white_conductor(roughness).into()));
+    materials.extend(
+        [0.1, 0.3, 0.6].map(|roughness| RoughDielectricMaterial::new(1.5, roughness).into()),
+    );
+
+    materials
+}
+
+/// Measures every material of [`materials`].
+pub fn report(samples_per_pixel: usize) -> Vec<FurnaceResult> {
+    materials()
+        .into_iter()
+        .map(|material| measure(material, samples_per_pixel))
+        .collect()
+}
@@ -0,0 +1,99 @@
+//! Animated GIF files, small enough to share animations in code review.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+    time::Duration,
+};
+
+use gif::{DisposalMethod, Encoder, EncodingError, Frame, Repeat};
+
+use crate::{
+    file,
+    ppm::{self, PPMImage},
+    quantize::{Dither, Palette, Quantizer, MAX_COLORS},
+};
+
+/// How frames are turned into a GIF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GifOptions {
+    /// How long each frame is shown, rounded to hundredths of a second.
+    pub delay: Duration,
+    /// Size of the palette shared by every frame, up to 256.
+    pub colors: usize,
+    pub quantizer: Quantizer,
+    pub dither: Dither,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(40),
+            colors: MAX_COLORS,
+            quantizer: Quantizer::default(),
+            dither: Dither::default(),
+        }
+    }
+}
+
+/// Writes `frames` as an animated GIF which loops forever. Every frame must
+/// have the size of the first one. A single palette is chosen for all of
+/// them, so that colors do not flicker from frame to frame.
+pub fn write_gif(
+    frames: &[PPMImage],
+    options: &GifOptions,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let (width, height) = ppm::frame_size(frames)?;
+    let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "GIF frames are at most 65535 pixels wide and high",
+            ))
+        }
+    };
+
+    let palette = Palette::new(frames, options.colors, options.quantizer);
+    let table: Vec<u8> = palette
+        .colors()
+        .iter()
+        .flat_map(|color| [color.r(), color.g(), color.b()])
+        .collect();
+
+    let mut encoder = Encoder::new(writer, width, height, &table).map_err(encoding_error)?;
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(encoding_error)?;
+
+    let delay = (options.delay.as_millis() / 10).min(u128::from(u16::MAX)) as u16;
+
+    for frame in frames {
+        // Every frame covers the whole image and uses the global palette.
+        let frame = Frame {
+            delay,
+            dispose: DisposalMethod::Keep,
+            width,
+            height,
+            buffer: palette.indices(frame, options.dither).into(),
+            ..Frame::default()
+        };
+
+        encoder.write_frame(&frame).map_err(encoding_error)?;
+    }
+
+    encoder.into_inner().map(drop)
+}
+
+/// Saves `frames` to an animated GIF file, replacing it at once.
+pub fn save_gif(frames: &[PPMImage], options: &GifOptions, path: &Path) -> io::Result<()> {
+    file::replace(path, |writer| write_gif(frames, options, writer))
+}
+
+fn encoding_error(error: EncodingError) -> io::Error {
+    match error {
+        EncodingError::Io(error) => error,
+        EncodingError::Format(error) => io::Error::new(io::ErrorKind::InvalidInput, error),
+    }
+}
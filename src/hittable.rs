@@ -1,9 +1,12 @@
+//! Objects which rays can hit.
+
 use std::fmt::Debug;
 
 use crate::{
     float::Fl, hittable_list::HittableList, interval::Interval, material::MaterialObject, ray::Ray, sphere::Sphere, vec3::{Pos3, Vec3}
 };
 
+/// Where and how a ray hit an object.
 #[derive(Debug, Clone)]
 pub struct HitRecord {
     pub t: Fl,
@@ -11,6 +14,9 @@ pub struct HitRecord {
     pub mat: MaterialObject,
     pub front_face: bool,
     pub normal: Vec3,
+    /// Index of the sphere which was hit, counting the spheres of the world
+    /// depth-first.
+    pub object_id: usize,
 }
 
 impl HitRecord {
@@ -21,6 +27,7 @@ impl HitRecord {
             mat,
             front_face: bool::default(),
             normal: Vec3::default(),
+            object_id: 0,
         };
 
         rec.set_face_normal(r, outward_normal);
@@ -39,10 +46,13 @@ impl HitRecord {
     }
 }
 
+/// An object which rays can hit.
 pub trait Hittable: Debug + Send + Sync {
+    /// Returns the closest hit of the ray with `t` inside `ray_t`.
     fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord>;
 }
 
+/// Any of the hittable objects.
 #[derive(Debug, Clone)]
 pub enum HittableObject {
     Sphere(Sphere),
@@ -69,3 +79,23 @@ impl Hittable for HittableObject {
         }
     }
 }
+
+impl HittableObject {
+    /// Count of spheres in the object, which is also the count of object IDs
+    /// it uses.
+    pub fn leaf_count(&self) -> usize {
+        match self {
+            HittableObject::Sphere(_) => 1,
+            HittableObject::List(i) => i.leaf_count(),
+        }
+    }
+
+    /// The spheres of the object, depth-first, so that a sphere's index is
+    /// its object ID.
+    pub fn spheres(&self) -> Vec<&Sphere> {
+        match self {
+            HittableObject::Sphere(i) => vec![i],
+            HittableObject::List(i) => i.objects().iter().flat_map(Self::spheres).collect(),
+        }
+    }
+}
@@ -1,28 +1,47 @@
+//! A group of objects.
+
 use crate::{
     hittable::{HitRecord, Hittable, HittableObject},
     interval::Interval,
     ray::Ray,
 };
 
+/// A group of objects, hit by a ray at the closest of them.
 #[derive(Debug, Clone, Default)]
 pub struct HittableList {
     objects: Vec<HittableObject>,
+    /// The first object ID of each object.
+    first_ids: Vec<usize>,
+    leaf_count: usize,
 }
 
 impl HittableList {
     pub fn new(object: HittableObject) -> Self {
-        Self {
-            objects: vec![object],
-        }
+        let mut list = Self::default();
+        list.add(object);
+        list
     }
 
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.first_ids.clear();
+        self.leaf_count = 0;
     }
 
     pub fn add(&mut self, object: HittableObject) {
+        self.first_ids.push(self.leaf_count);
+        self.leaf_count += object.leaf_count();
         self.objects.push(object);
     }
+
+    /// Count of spheres in the list, including those in nested lists.
+    pub const fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn objects(&self) -> &[HittableObject] {
+        &self.objects
+    }
 }
 
 impl Hittable for HittableList {
@@ -30,9 +49,10 @@ impl Hittable for HittableList {
         let mut closest_rec: Option<HitRecord> = None;
         let mut closest_so_far = ray_t.get_max();
 
-        for object in &self.objects {
-            if let Some(rec) = object.hit(r, Interval::new(ray_t.get_min(), closest_so_far)) {
+        for (object, first_id) in self.objects.iter().zip(&self.first_ids) {
+            if let Some(mut rec) = object.hit(r, Interval::new(ray_t.get_min(), closest_so_far)) {
                 closest_so_far = rec.t;
+                rec.object_id += first_id;
                 closest_rec = Some(rec);
             }
         }
@@ -1,5 +1,9 @@
+//! Closed intervals of real numbers.
+
 use crate::float::{lerp, random, Fl};
 
+/// The interval `[min, max]`. It is empty if `min > max`.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct Interval {
     min: Fl,
     max: Fl,
@@ -10,6 +14,7 @@ impl Interval {
         Self { min, max }
     }
 
+    /// An interval which contains nothing.
     pub const fn new_empty() -> Self {
         Self {
             min: Fl::INFINITY,
@@ -17,6 +22,7 @@ impl Interval {
         }
     }
 
+    /// An interval which contains every number.
     pub const fn new_universe() -> Self {
         Self {
             min: Fl::NEG_INFINITY,
@@ -40,6 +46,7 @@ impl Interval {
         self.min <= x && x <= self.max
     }
 
+    /// Like [`Interval::contains`], but excluding the bounds.
     pub fn surrounds(&self, x: Fl) -> bool {
         self.min < x && x < self.max
     }
@@ -48,6 +55,7 @@ impl Interval {
         x.clamp(self.min, self.max)
     }
 
+    /// Returns a random number in `[min, max)`.
     pub fn random(&self) -> Fl {
         lerp(random(), self.get_min(), self.get_max())
     }
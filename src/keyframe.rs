@@ -0,0 +1,97 @@
+//! Values which change over scene time, given at a few key times.
+
+use std::ops::{Add, Mul, Sub};
+
+use crate::float::Fl;
+
+/// How values between two keys are computed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Straight lines between keys, with sudden changes of speed at keys.
+    #[default]
+    Linear,
+    /// A Catmull-Rom spline through the keys, with smooth changes of speed.
+    CatmullRom,
+}
+
+/// Values at key times. Before the first key and after the last one, the
+/// value is held.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track<T> {
+    keys: Vec<(Fl, T)>,
+    interpolation: Interpolation,
+}
+
+impl<T> Track<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Fl, Output = T>,
+{
+    /// A track through the given `(time, value)` keys, in any order.
+    ///
+    /// Panics if there are no keys.
+    pub fn new(mut keys: Vec<(Fl, T)>, interpolation: Interpolation) -> Self {
+        assert!(!keys.is_empty(), "a track needs at least one key");
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Self {
+            keys,
+            interpolation,
+        }
+    }
+
+    /// A track which always has the same value.
+    pub fn constant(value: T) -> Self {
+        Self::new(vec![(0., value)], Interpolation::Linear)
+    }
+
+    pub fn keys(&self) -> &[(Fl, T)] {
+        &self.keys
+    }
+
+    pub const fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// The value at `time`.
+    pub fn at(&self, time: Fl) -> T {
+        let keys = &self.keys;
+        let last = keys.len() - 1;
+
+        // Index of the first key after `time`.
+        let next = keys.partition_point(|&(t, _)| t <= time);
+
+        if next == 0 {
+            return keys[0].1;
+        }
+
+        if next > last {
+            return keys[last].1;
+        }
+
+        let i = next - 1;
+        let ((t0, p0), (t1, p1)) = (keys[i], keys[i + 1]);
+        let h = t1 - t0;
+        let s = (time - t0) / h;
+
+        match self.interpolation {
+            Interpolation::Linear => p0 + (p1 - p0) * s,
+            Interpolation::CatmullRom => {
+                // Tangents from the neighbouring keys, scaled to the length of
+                // this segment, so uneven key spacing keeps the speed smooth.
+                let (t_prev, p_prev) = if i > 0 { keys[i - 1] } else { (t0, p0) };
+                let (t_next, p_next) = if i + 1 < last { keys[i + 2] } else { (t1, p1) };
+
+                let m0 = (p1 - p_prev) * (h / (t1 - t_prev));
+                let m1 = (p_next - p0) * (h / (t_next - t0));
+
+                let s2 = s * s;
+                let s3 = s2 * s;
+
+                p0 * (2. * s3 - 3. * s2 + 1.)
+                    + m0 * (s3 - 2. * s2 + s)
+                    + p1 * (-2. * s3 + 3. * s2)
+                    + m1 * (s3 - s2)
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+//! Diffuse surfaces.
+
 use crate::{
     hittable::HitRecord,
     material::{Material, ScatterRecord},
@@ -5,6 +7,7 @@ use crate::{
     vec3::{Color, Vec3},
 };
 
+/// An ideal diffuse surface.
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct LambertianMaterial {
     albedo: Color,
@@ -14,9 +17,17 @@ impl LambertianMaterial {
     pub fn new(albedo: Color) -> Self {
         Self { albedo }
     }
+
+    pub const fn albedo(&self) -> Color {
+        self.albedo
+    }
 }
 
 impl Material for LambertianMaterial {
+    fn albedo(&self) -> Color {
+        self.albedo
+    }
+
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         let mut scatter_direction = rec.normal + Vec3::random_normalized();
 
@@ -28,6 +39,7 @@ impl Material for LambertianMaterial {
             attenuation: self.albedo,
             scattered: Ray::new(rec.p, scatter_direction)
                 .at_time_of(r_in)
+                .in_media_of(r_in)
                 .for_pixel_of(r_in),
         })
     }
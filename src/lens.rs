@@ -0,0 +1,705 @@
+//! Camera lenses made of spherical elements, through which every ray is
+//! traced, for the distortion, aberrations and focus breathing of real
+//! lenses.
+//!
+//! Lens space is in millimeters, with the film at `z = 0`, the optical axis
+//! along `z` and the scene towards positive `z`.
+
+use std::{
+    error::Error,
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+};
+
+use crate::{
+    float::{lerp, random, Fl},
+    vec3::{Pos3, Vec3},
+};
+
+/// Millimeters in a unit of the scene, whose units are taken as meters.
+pub const MILLIMETERS_PER_UNIT: Fl = 1000.;
+
+/// Diagonal of a full-frame 35 mm film, 36 by 24 mm.
+pub const FULL_FRAME_DIAGONAL: Fl = 43.267;
+
+/// Rings of the film for which the exit pupil is bounded.
+const PUPIL_INTERVALS: usize = 64;
+/// Points on each side of the square around the rear element tried when
+/// bounding the exit pupil.
+const PUPIL_GRID: usize = 128;
+/// Steps of the search of the film distance which focuses the lens.
+const FOCUS_STEPS: usize = 32;
+
+/// The Tronnier F/2 double-Gauss of US patent 2,673,491, at 100 mm, from
+/// Smith, Modern Lens Design, p. 312.
+const DOUBLE_GAUSS_100MM: &str = "\
+# radius  thickness  ior    aperture
+  58.950  7.520      1.670  50.4
+ 169.660  0.240      1      50.4
+  38.550  8.050      1.670  46.0
+  81.540  6.550      1.699  46.0
+  25.500  11.410     1      36.0
+   0      9.000      0      34.2
+ -28.990  2.360      1.603  34.0
+  81.540  12.130     1.658  40.0
+ -40.770  0.380      1      40.0
+ 874.130  6.440      1.717  40.0
+ -79.460  72.228     1      40.0
+";
+
+/// One surface of a lens, as a row of a prescription.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LensElement {
+    /// Radius of curvature in millimeters, positive when the center is
+    /// towards the film. Zero for the aperture stop, which is flat.
+    pub radius: Fl,
+    /// Distance along the axis to the next surface, or to the film for the
+    /// last one, in millimeters.
+    pub thickness: Fl,
+    /// Index of refraction between this surface and the next, 1 for air.
+    pub ior: Fl,
+    /// Diameter of the surface in millimeters.
+    pub aperture: Fl,
+}
+
+impl LensElement {
+    pub fn is_stop(&self) -> bool {
+        self.radius == 0.
+    }
+}
+
+/// Why a lens prescription could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LensParseError {
+    /// A row does not have a radius, thickness, index and aperture.
+    InvalidRow(String),
+    /// There are no rows.
+    Empty,
+}
+
+impl Display for LensParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LensParseError::InvalidRow(row) => {
+                write!(f, "expected radius, thickness, ior and aperture in {row:?}")
+            }
+            LensParseError::Empty => write!(f, "the lens has no elements"),
+        }
+    }
+}
+
+impl Error for LensParseError {}
+
+/// The surfaces of a lens from the front, facing the scene, to the rear,
+/// facing the film.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LensSystem {
+    elements: Vec<LensElement>,
+}
+
+/// A ray in lens space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LensRay {
+    pub origin: Pos3,
+    pub direction: Vec3,
+}
+
+impl LensSystem {
+    /// Names accepted by [`LensSystem::from_name`].
+    pub const NAMES: [&'static str; 2] = ["double-gauss-50mm", "double-gauss-100mm"];
+
+    /// Returns the built-in lens with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "double-gauss-50mm" => Some(Self::double_gauss_50mm()),
+            "double-gauss-100mm" => Some(Self::double_gauss_100mm()),
+            _ => None,
+        }
+    }
+
+    /// A lens with the given surfaces, where an index of refraction of 0
+    /// stands for air, as in many prescriptions.
+    pub fn new(elements: Vec<LensElement>) -> Self {
+        let elements = elements
+            .into_iter()
+            .map(|element| LensElement {
+                ior: if element.ior == 0. { 1. } else { element.ior },
+                ..element
+            })
+            .collect();
+
+        Self { elements }
+    }
+
+    /// The classic F/2 double-Gauss at its original focal length of 100 mm.
+    pub fn double_gauss_100mm() -> Self {
+        DOUBLE_GAUSS_100MM
+            .parse()
+            .expect("the built-in prescription is valid")
+    }
+
+    /// The classic F/2 double-Gauss scaled to a normal lens of 50 mm.
+    pub fn double_gauss_50mm() -> Self {
+        Self::double_gauss_100mm().scaled(0.5)
+    }
+
+    pub fn elements(&self) -> &[LensElement] {
+        &self.elements
+    }
+
+    /// The same lens with every length multiplied by `factor`.
+    pub fn scaled(&self, factor: Fl) -> Self {
+        let elements = self
+            .elements
+            .iter()
+            .map(|element| LensElement {
+                radius: element.radius * factor,
+                thickness: element.thickness * factor,
+                aperture: element.aperture * factor,
+                ..*element
+            })
+            .collect();
+
+        Self { elements }
+    }
+
+    /// The same lens with its aperture stop closed down to `diameter`
+    /// millimeters, if it is wider.
+    pub fn stopped_down(&self, diameter: Fl) -> Self {
+        let elements = self
+            .elements
+            .iter()
+            .map(|element| match element.is_stop() {
+                true => LensElement {
+                    aperture: element.aperture.min(diameter),
+                    ..*element
+                },
+                false => *element,
+            })
+            .collect();
+
+        Self { elements }
+    }
+
+    /// Position along the axis of the vertex of every surface, when the
+    /// rear one is `film_distance` from the film.
+    fn vertices(&self, film_distance: Fl) -> Vec<Fl> {
+        let mut vertices = vec![film_distance; self.elements.len()];
+
+        for i in (0..self.elements.len().saturating_sub(1)).rev() {
+            vertices[i] = vertices[i + 1] + self.elements[i].thickness;
+        }
+
+        vertices
+    }
+
+    /// Traces `ray` through every surface, from the film to the scene or the
+    /// other way. `None` if it hits the barrel or is totally reflected.
+    pub(crate) fn trace(
+        &self,
+        film_distance: Fl,
+        ray: LensRay,
+        towards_scene: bool,
+    ) -> Option<LensRay> {
+        let vertices = self.vertices(film_distance);
+        let n = self.elements.len();
+        let order: Box<dyn Iterator<Item = usize>> = if towards_scene {
+            Box::new((0..n).rev())
+        } else {
+            Box::new(0..n)
+        };
+
+        let LensRay {
+            mut origin,
+            mut direction,
+        } = ray;
+
+        for i in order {
+            let element = &self.elements[i];
+            let vertex = vertices[i];
+
+            let (t, normal) = if element.is_stop() {
+                ((vertex - origin.z()) / direction.z(), Vec3::new(0., 0., 1.))
+            } else {
+                intersect_surface(origin, direction, vertex, element.radius)?
+            };
+
+            if t.is_nan() || t <= 0. {
+                return None;
+            }
+
+            let p = origin + direction * t;
+            let half_aperture = element.aperture / 2.;
+
+            if p.x() * p.x() + p.y() * p.y() > half_aperture * half_aperture {
+                return None;
+            }
+
+            origin = p;
+
+            if !element.is_stop() {
+                // The medium in front of surface `i`, towards the scene.
+                let front = if i > 0 { self.elements[i - 1].ior } else { 1. };
+                let (eta_i, eta_t) = if towards_scene {
+                    (element.ior, front)
+                } else {
+                    (front, element.ior)
+                };
+
+                direction = refract(direction.normalize(), normal, eta_i / eta_t)?;
+            }
+        }
+
+        Some(LensRay { origin, direction })
+    }
+
+    /// Position along the axis where a ray parallel to the axis, from the
+    /// scene, crosses it, and where it seems to bend, for a film at
+    /// `film_distance`.
+    fn rear_cardinal_points(&self, film_distance: Fl) -> Option<(Fl, Fl)> {
+        let front = self.elements.first()?;
+        let height = front.aperture * 1e-3;
+        let start = self.vertices(film_distance)[0] + 1.;
+
+        let out = self.trace(
+            film_distance,
+            LensRay {
+                origin: Pos3::new(height, 0., start),
+                direction: Vec3::new(0., 0., -1.),
+            },
+            false,
+        )?;
+
+        let focal_point = out.origin.z() - out.origin.x() / out.direction.x() * out.direction.z();
+        let principal_plane =
+            out.origin.z() + (height - out.origin.x()) / out.direction.x() * out.direction.z();
+
+        Some((focal_point, principal_plane))
+    }
+
+    /// Effective focal length in millimeters.
+    pub fn focal_length(&self) -> Option<Fl> {
+        let (focal_point, principal_plane) = self.rear_cardinal_points(0.)?;
+        Some(principal_plane - focal_point)
+    }
+
+    /// Distance from the rear surface to the film which focuses the lens on
+    /// `distance` millimeters in front of the film, or `None` if the lens
+    /// cannot focus there.
+    pub fn film_distance(&self, distance: Fl) -> Option<Fl> {
+        let front = self.elements.first()?;
+        let height = front.aperture * 1e-3;
+        let (focal_point, _) = self.rear_cardinal_points(0.)?;
+
+        if distance.is_infinite() {
+            return (focal_point < 0.).then_some(-focal_point);
+        }
+
+        // Starting from focus at infinity, move the film to where the image
+        // of the point on the axis forms, until it stays there.
+        let mut film_distance = -focal_point;
+
+        for _step in 0..FOCUS_STEPS {
+            let front_z = self.vertices(film_distance)[0];
+
+            if distance <= front_z {
+                return None;
+            }
+
+            let origin = Pos3::new(0., 0., distance);
+            let out = self.trace(
+                film_distance,
+                LensRay {
+                    origin,
+                    direction: Pos3::new(height, 0., front_z) - origin,
+                },
+                false,
+            )?;
+
+            let image = out.origin.z() - out.origin.x() / out.direction.x() * out.direction.z();
+            film_distance -= image;
+
+            if image.abs() < 1e-9 {
+                break;
+            }
+        }
+
+        (film_distance > 0.).then_some(film_distance)
+    }
+
+    /// Bounds the exit pupil seen from the ring `interval` of `PUPIL_INTERVALS`
+    /// rings of a film of radius `film_radius`, for film points on the
+    /// positive `x` axis.
+    fn pupil_ring(&self, film_distance: Fl, film_radius: Fl, interval: usize) -> PupilRing {
+        let Some(rear) = self.elements.last() else {
+            return PupilRing::default();
+        };
+
+        // Rays can get through off the rear surface itself when it curves
+        // towards the film.
+        let extent = rear.aperture / 2. * 1.5;
+        let cell = 2. * extent / PUPIL_GRID as Fl;
+
+        let r0 = film_radius * interval as Fl / PUPIL_INTERVALS as Fl;
+        let r1 = film_radius * (interval + 1) as Fl / PUPIL_INTERVALS as Fl;
+        let mut ring = PupilRing::default();
+
+        for i in 0..PUPIL_GRID {
+            for j in 0..PUPIL_GRID {
+                let point = (
+                    -extent + (i as Fl + 0.5) * cell,
+                    -extent + (j as Fl + 0.5) * cell,
+                );
+                let fraction = (i * PUPIL_GRID + j) as Fl / (PUPIL_GRID * PUPIL_GRID) as Fl;
+                let film = Pos3::new(lerp(fraction, r0, r1), 0., 0.);
+
+                let ray = LensRay {
+                    origin: film,
+                    direction: Pos3::new(point.0, point.1, film_distance) - film,
+                };
+
+                if self.trace(film_distance, ray, true).is_some() {
+                    ring.bounds = Some(match ring.bounds {
+                        Some(bounds) => bounds.including(point),
+                        None => PupilBounds {
+                            min: point,
+                            max: point,
+                        },
+                    });
+                    ring.area += ray.direction.normalize().z().powi(4) * cell * cell;
+                }
+            }
+        }
+
+        // Points between the ones tried may get through too.
+        ring.bounds = ring.bounds.map(|bounds| bounds.expanded(cell));
+        ring
+    }
+}
+
+/// Reads a prescription with a surface per line, from the front: radius,
+/// thickness, index of refraction and aperture diameter, in millimeters.
+/// Everything after a `#` is a comment.
+impl FromStr for LensSystem {
+    type Err = LensParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s
+            .lines()
+            .map(|line| line.split_once('#').map_or(line, |(line, _comment)| line))
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let values: Vec<Fl> = line
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| LensParseError::InvalidRow(line.to_owned()))?;
+
+                match values[..] {
+                    [radius, thickness, ior, aperture] => Ok(LensElement {
+                        radius,
+                        thickness,
+                        ior,
+                        aperture,
+                    }),
+                    _ => Err(LensParseError::InvalidRow(line.to_owned())),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if elements.is_empty() {
+            return Err(LensParseError::Empty);
+        }
+
+        Ok(Self::new(elements))
+    }
+}
+
+/// Where a ray meets a spherical surface whose vertex is at `vertex` on the
+/// axis, with the normal there facing the ray.
+fn intersect_surface(origin: Pos3, direction: Vec3, vertex: Fl, radius: Fl) -> Option<(Fl, Vec3)> {
+    let center = Pos3::new(0., 0., vertex - radius);
+    let oc = origin - center;
+    let a = direction.squared_abs();
+    let h = direction.dot(&oc);
+    let c = oc.squared_abs() - radius * radius;
+    let discriminant = h * h - a * c;
+
+    if discriminant < 0. {
+        return None;
+    }
+
+    // The vertex is on the side of the sphere away from its center.
+    let sqrt_d = discriminant.sqrt();
+    let closer = (direction.z() > 0.) != (radius > 0.);
+    let root = if closer { -h - sqrt_d } else { -h + sqrt_d };
+    let t = root / a;
+
+    let normal = (origin + direction * t - center).normalize();
+    let normal = if normal.dot(&direction) > 0. {
+        -normal
+    } else {
+        normal
+    };
+
+    Some((t, normal))
+}
+
+/// The refracted direction of the unit `direction` through a surface whose
+/// unit `normal` faces it, or `None` on total internal reflection.
+fn refract(direction: Vec3, normal: Vec3, eta: Fl) -> Option<Vec3> {
+    let cos_i = -direction.dot(&normal);
+    let sin2_t = eta * eta * (1. - cos_i * cos_i);
+
+    if sin2_t > 1. {
+        return None;
+    }
+
+    let cos_t = (1. - sin2_t).sqrt();
+    Some(direction * eta + normal * (eta * cos_i - cos_t))
+}
+
+/// The exit pupil seen from a ring of the film.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct PupilRing {
+    /// Bounds of the points of the plane of the rear surface through which
+    /// rays get through the lens, or `None` if none does.
+    bounds: Option<PupilBounds>,
+    /// Area of the plane through which rays get through, each point weighted
+    /// by the cosine to the fourth of its ray, as samples are.
+    area: Fl,
+}
+
+/// The exit pupil of a lens whose film is at `film_distance` from the rear
+/// surface. Each ring is only bounded once a ray is traced from it, as
+/// auto-focus only needs a few of them.
+#[derive(Debug)]
+struct ExitPupil {
+    film_distance: Fl,
+    film_radius: Fl,
+    rings: Vec<OnceLock<PupilRing>>,
+}
+
+impl ExitPupil {
+    fn new(film_distance: Fl, film_radius: Fl) -> Self {
+        Self {
+            film_distance,
+            film_radius,
+            rings: (0..PUPIL_INTERVALS).map(|_| OnceLock::new()).collect(),
+        }
+    }
+
+    fn ring(&self, system: &LensSystem, interval: usize) -> PupilRing {
+        *self.rings[interval]
+            .get_or_init(|| system.pupil_ring(self.film_distance, self.film_radius, interval))
+    }
+}
+
+/// Exit pupils already bounded for a lens, shared by its clones so that the
+/// cameras of every frame reuse them.
+#[derive(Debug, Default, Clone)]
+struct PupilCache(Arc<Mutex<Vec<Arc<ExitPupil>>>>);
+
+impl PupilCache {
+    fn get(&self, film_distance: Fl, film_radius: Fl) -> Arc<ExitPupil> {
+        let mut pupils = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(pupil) = pupils
+            .iter()
+            .find(|pupil| pupil.film_distance == film_distance && pupil.film_radius == film_radius)
+        {
+            return Arc::clone(pupil);
+        }
+
+        let pupil = Arc::new(ExitPupil::new(film_distance, film_radius));
+        pupils.push(Arc::clone(&pupil));
+        pupil
+    }
+}
+
+/// A cache never makes lenses different.
+impl PartialEq for PupilCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// A rectangle on the plane of the rear surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PupilBounds {
+    min: (Fl, Fl),
+    max: (Fl, Fl),
+}
+
+impl PupilBounds {
+    fn including(self, point: (Fl, Fl)) -> Self {
+        Self {
+            min: (self.min.0.min(point.0), self.min.1.min(point.1)),
+            max: (self.max.0.max(point.0), self.max.1.max(point.1)),
+        }
+    }
+
+    fn expanded(self, margin: Fl) -> Self {
+        Self {
+            min: (self.min.0 - margin, self.min.1 - margin),
+            max: (self.max.0 + margin, self.max.1 + margin),
+        }
+    }
+
+    fn area(&self) -> Fl {
+        (self.max.0 - self.min.0) * (self.max.1 - self.min.1)
+    }
+}
+
+/// A real lens in front of a film, instead of the thin lens of the camera.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealisticLens {
+    pub system: Arc<LensSystem>,
+    /// Diagonal of the film in millimeters, which with the focal length sets
+    /// the field of view.
+    pub film_diagonal: Fl,
+    pupils: PupilCache,
+}
+
+impl RealisticLens {
+    pub fn new(system: LensSystem) -> Self {
+        Self {
+            system: Arc::new(system),
+            film_diagonal: FULL_FRAME_DIAGONAL,
+            pupils: PupilCache::default(),
+        }
+    }
+}
+
+/// What focuses light on the film.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum Lens {
+    /// An ideal lens of no thickness, shaped by the field of view, defocus
+    /// angle and aperture of the camera.
+    #[default]
+    Thin,
+    /// A system of spherical elements, focused at the focus distance of the
+    /// camera in the middle of the exposure, measured from the film. The
+    /// field of view, intrinsics, distortion, defocus angle and aperture of
+    /// the camera are ignored, as the lens has its own.
+    Realistic(RealisticLens),
+}
+
+/// A realistic lens focused and ready to trace rays from the film.
+#[derive(Debug, Clone)]
+pub(crate) struct LensCamera {
+    system: Arc<LensSystem>,
+    film_distance: Fl,
+    film_width: Fl,
+    film_height: Fl,
+    pupil: Arc<ExitPupil>,
+}
+
+impl LensCamera {
+    /// Focuses `lens` on `focus_dist` scene units from the film, for an image
+    /// of the given aspect ratio. If the lens cannot focus that close, it is
+    /// focused at infinity.
+    pub(crate) fn new(lens: &RealisticLens, focus_dist: Fl, aspect_ratio: Fl) -> Self {
+        let system = Arc::clone(&lens.system);
+        let film_distance = system
+            .film_distance(focus_dist * MILLIMETERS_PER_UNIT)
+            .or_else(|| system.film_distance(Fl::INFINITY))
+            .unwrap_or_else(|| system.elements.last().map_or(0., |rear| rear.thickness));
+
+        let film_height = lens.film_diagonal / (1. + aspect_ratio * aspect_ratio).sqrt();
+        let film_width = film_height * aspect_ratio;
+
+        let pupil = lens.pupils.get(film_distance, lens.film_diagonal / 2.);
+
+        Self {
+            system,
+            film_distance,
+            film_width,
+            film_height,
+            pupil,
+        }
+    }
+
+    /// The point of the film seen at `(s, t)` of the image, from its top
+    /// left corner to its bottom right one. The lens turns the image upside
+    /// down, so the film is too.
+    fn film_point(&self, s: Fl, t: Fl) -> Pos3 {
+        Pos3::new(
+            (0.5 - s) * self.film_width,
+            (t - 0.5) * self.film_height,
+            0.,
+        )
+    }
+
+    /// The bounds of the exit pupil seen from `film`, rotated to the
+    /// positive `x` axis, and the cosine and sine of the rotation.
+    fn pupil_at(&self, film: Pos3) -> Option<(PupilBounds, Fl, Fl)> {
+        let radius = film.x().hypot(film.y());
+        let interval = (radius / (self.film_diagonal() / 2.) * PUPIL_INTERVALS as Fl) as usize;
+        let bounds = self
+            .pupil
+            .ring(&self.system, interval.min(PUPIL_INTERVALS - 1))
+            .bounds?;
+
+        let (cos, sin) = if radius > 0. {
+            (film.x() / radius, film.y() / radius)
+        } else {
+            (1., 0.)
+        };
+
+        Some((bounds, cos, sin))
+    }
+
+    fn film_diagonal(&self) -> Fl {
+        self.film_width.hypot(self.film_height)
+    }
+
+    /// Traces a ray from the film at `(s, t)` through a random point of the
+    /// exit pupil. Returns the ray leaving the front of the lens and its
+    /// weight, or `None` if the lens blocks it. Weights average to one at the
+    /// center of the film, and less towards the corners as a real lens
+    /// darkens them.
+    pub(crate) fn sample(&self, s: Fl, t: Fl) -> Option<(LensRay, Fl)> {
+        let film = self.film_point(s, t);
+        let (bounds, cos, sin) = self.pupil_at(film)?;
+
+        let x = lerp(random(), bounds.min.0, bounds.max.0);
+        let y = lerp(random(), bounds.min.1, bounds.max.1);
+        let pupil = Pos3::new(x * cos - y * sin, x * sin + y * cos, self.film_distance);
+
+        let direction = pupil - film;
+        let cos_theta = direction.normalize().z();
+        let center_area = self.pupil.ring(&self.system, 0).area;
+        let weight = cos_theta.powi(4) * bounds.area() / center_area;
+
+        let ray = LensRay {
+            origin: film,
+            direction,
+        };
+
+        self.system
+            .trace(self.film_distance, ray, true)
+            .map(|ray| (ray, weight))
+    }
+
+    /// The ray from the film at `(s, t)` through the center of the exit
+    /// pupil, which sees what is in focus there.
+    pub(crate) fn chief_ray(&self, s: Fl, t: Fl) -> Option<LensRay> {
+        let film = self.film_point(s, t);
+        let (bounds, cos, sin) = self.pupil_at(film)?;
+
+        let x = (bounds.min.0 + bounds.max.0) / 2.;
+        let y = (bounds.min.1 + bounds.max.1) / 2.;
+        let pupil = Pos3::new(x * cos - y * sin, x * sin + y * cos, self.film_distance);
+
+        self.system.trace(
+            self.film_distance,
+            LensRay {
+                origin: film,
+                direction: pupil - film,
+            },
+            true,
+        )
+    }
+}
@@ -0,0 +1,97 @@
+//! A path tracer following the [Ray Tracing in One Weekend] tutorial.
+//!
+//! A render is made of a world, which is any [`HittableObject`] (usually a
+//! [`HittableList`] of [`Sphere`]s), and a [`Camera`] built from
+//! [`CameraOptions`]. The camera produces a [`PPMImage`] which can be written
+//! out with its [`Display`](std::fmt::Display) implementation.
+//!
+//! ```no_run
+//! use path_tracing::prelude::*;
+//!
+//! let mut world = HittableList::default();
+//! let material: MaterialObject = LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into();
+//! world.add(Sphere::new(Pos3::new(0., 0., -1.), 0.5, material).into());
+//!
+//! let camera = Camera::new(CameraOptions::default());
+//! let image = camera.render_parallel(&world.into());
+//!
+//! println!("{image}");
+//! ```
+//!
+//! [Ray Tracing in One Weekend]: https://raytracing.github.io/books/RayTracingInOneWeekend.html
+
+pub mod accumulator;
+pub mod animation;
+pub mod aov;
+pub mod aperture;
+pub mod apng;
+pub mod camera;
+pub mod conductor;
+pub mod crop;
+pub mod denoise;
+pub mod dielectric;
+pub mod distortion;
+mod file;
+pub mod fish;
+pub mod float;
+pub mod float_image;
+pub mod furnace;
+pub mod gif;
+pub mod hittable;
+pub mod hittable_list;
+pub mod interval;
+pub mod keyframe;
+pub mod lambertian;
+pub mod lens;
+pub mod material;
+pub mod medium;
+pub mod metal;
+pub mod metrics;
+pub mod microfacet;
+pub mod ppm;
+pub mod prelude;
+pub mod progressive;
+pub mod projection;
+pub mod quantize;
+pub mod ray;
+pub mod rough_dielectric;
+pub mod scene;
+pub mod shutter;
+pub mod sphere;
+pub mod stats;
+pub mod tile;
+pub mod vec3;
+
+pub use accumulator::{AccumulatedColor, Accumulator};
+pub use animation::{AnimatedMaterial, AnimatedSphere, Animation, FrameStatus, Timeline};
+pub use aov::Aovs;
+pub use aperture::{Aperture, ApertureMask, ApertureShape};
+pub use camera::{AutoFocus, Background, Camera, CameraMotion, CameraOptions};
+pub use conductor::ConductorMaterial;
+pub use crop::Crop;
+pub use denoise::DenoiseOptions;
+pub use dielectric::DielectricMaterial;
+pub use distortion::{Distortion, Intrinsics};
+pub use float_image::FloatImage;
+pub use gif::GifOptions;
+pub use hittable::{HitRecord, Hittable, HittableObject};
+pub use hittable_list::HittableList;
+pub use interval::Interval;
+pub use keyframe::{Interpolation, Track};
+pub use lambertian::LambertianMaterial;
+pub use lens::{Lens, LensElement, LensParseError, LensSystem, RealisticLens};
+pub use material::{Material, MaterialObject, ScatterRecord};
+pub use medium::{Medium, MediumStack};
+pub use metal::MetalMaterial;
+pub use ppm::{PPMColor, PPMImage};
+pub use progressive::{ProgressiveOptions, StopReason};
+pub use projection::{FisheyeModel, Projection};
+pub use quantize::{Dither, Palette, Quantizer};
+pub use ray::Ray;
+pub use rough_dielectric::RoughDielectricMaterial;
+pub use scene::Scene;
+pub use shutter::{Shutter, ShutterCurve};
+pub use sphere::Sphere;
+pub use stats::RenderStats;
+pub use tile::{Tile, TileOptions, TileOrder};
+pub use vec3::{Color, Pos3, Vec3};
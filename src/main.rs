@@ -1,108 +1,822 @@
-mod camera;
-mod dielectric;
-mod float;
-mod hittable;
-mod hittable_list;
-mod interval;
-mod lambertian;
-mod material;
-mod metal;
-mod ppm;
-mod ray;
-mod sphere;
-mod vec3;
-use camera::{Camera, CameraOptions};
-use dielectric::DielectricMaterial;
-use float::{random, Fl};
-use hittable_list::HittableList;
-use interval::Interval;
-use lambertian::LambertianMaterial;
-use material::MaterialObject;
-use metal::MetalMaterial;
-use sphere::Sphere;
-use vec3::{Color, Pos3, Vec3};
-
-pub fn main() {
-    let mut world = HittableList::default();
-
-    let ground_material: MaterialObject = LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into();
-    world.add(Sphere::new(Pos3::new(0., -1000., 0.), 1000., ground_material).into());
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_mat = random();
-            let center = Pos3::new((a as Fl) + 0.9 * random(), 0.2, (b as Fl) + 0.9 * random());
-
-            if choose_mat < 0.8 {
-                world.add(
-                    Sphere::new_moving(
-                        center,
-                        center + Vec3::new(0., random() * 0.5, 0.),
-                        0.2,
-                        LambertianMaterial::new(Color::random()).into(),
-                    )
-                    .into(),
-                );
-            } else if choose_mat < 0.95 {
-                world.add(
-                    Sphere::new(
-                        center,
-                        0.2,
-                        MetalMaterial::new(
-                            Color::random_between(Interval::new(0.5, 1.)),
-                            Interval::new(0., 0.5).random(),
-                        )
-                        .into(),
-                    )
-                    .into(),
-                );
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use path_tracing::{
+    apng, denoise, fish, float::Fl, furnace, gif, metrics, Accumulator, Animation, ApertureMask,
+    ApertureShape, AutoFocus, Camera, CameraOptions, Crop, DenoiseOptions, Distortion, Dither,
+    FloatImage, FrameStatus, GifOptions, HittableObject, Intrinsics, Lens, LensSystem, PPMImage,
+    ProgressiveOptions, Projection, Quantizer, RealisticLens, Scene, ShutterCurve, Tile,
+    TileOptions, TileOrder,
+};
+
+/// Options of every command which renders, as a literal for `concat!`.
+macro_rules! camera_options {
+    () => {
+        "  --seed N                   make the render repeatable
+  --width N                  image width in pixels
+  --height N                 image height in pixels (default from the width)
+  --crop X,Y,WxH             render only these pixels of the image
+  --crop-window L,T,R,B      render only this fraction of the image
+  --shutter-open TIME        when the exposure starts (default 0)
+  --shutter-close TIME       when the exposure ends (default 1)
+  --shutter-curve CURVE      box (default) or triangle
+  --rolling-shutter TIME     time for the shutter to sweep down the image
+  --aperture-blades N        polygonal aperture with N blades
+  --aperture-rotation DEG    rotation of the polygonal aperture
+  --aperture-curvature X     0 for straight blades to 1 for a disk
+  --aperture-mask FILE       grayscale PPM image of the aperture
+  --cat-eye X                clipping of the aperture in the corners, 0 to 1
+  --projection NAME          perspective (default), equirectangular, fisheye,
+                             fisheye-equisolid or cube-map
+  --fisheye-fov DEGREES      angle covered by a fisheye (default 180)
+  --orthographic WxH         parallel rays from a W by H view plane
+  --intrinsics FX,FY,CX,CY   calibrated pinhole camera, in pixels of the plate
+  --plate WxH                size of the image the intrinsics are for
+  --brown-conrady K1,...     radial K1,K2,K3 and tangential P1,P2 distortion
+  --division K1,K2           division model distortion
+  --lens NAME                double-gauss-50mm, double-gauss-100mm or a file of
+                             radius, thickness, ior and aperture rows
+  --lens-stop MM             close the aperture stop of the lens to MM
+  --film-diagonal MM         size of the film behind the lens (default 43.267)
+  --auto-focus PIXEL         focus on what is seen at X,Y or the center
+  --focus-patch N            average the focus over N pixels around it
+  --tile-size N              width and height of the tiles handed out to threads
+  --tile-order ORDER         scanline, spiral (default) or hilbert
+"
+    };
+}
+
+const USAGE: &str = concat!(
+    "\
+usage: path-tracing [render [SCENE] [OPTIONS]]
+       path-tracing progressive SCENE [OPTIONS]
+       path-tracing animate ANIMATION PREFIX [OPTIONS]
+       path-tracing encode OUTPUT FRAME... [OPTIONS]
+       path-tracing merge-checkpoints OUTPUT INPUT...
+       path-tracing paste OUTPUT FRAME CROP X,Y
+       path-tracing compare IMAGE REFERENCE [HEATMAP]
+       path-tracing furnace [SAMPLES]
+       path-tracing fish-export SCENE
+       path-tracing fish-parity SCENE_FILE RANDOM_LOG FISH_PPM [TOLERANCE]
+
+render options:
+",
+    camera_options!(),
+    "  --samples N                samples per pixel
+  --checkpoint FILE          save progress to FILE, resuming from it if it exists
+  --interval SECONDS         time between checkpoints (default 60)
+  --aovs PREFIX              also write the first-hit buffers and the unclamped
+                             image to PREFIX.<name>.pfm
+  --denoise STRENGTH         filter the noise out, guided by the first-hit
+                             buffers (1 is a good start)
+  --denoise-iterations N     passes of the filter, each twice as wide (default 5)
+
+progressive options:
+",
+    camera_options!(),
+    "  --output FILE              write the image so far to FILE
+  --checkpoint FILE          save progress to FILE, resuming from it if it exists
+  --write-interval SECONDS   time between writes of the output and checkpoint
+  --pass-samples N           samples per pixel added in each pass
+  --budget SECONDS           stop once this much time is spent
+  --samples N                stop at this many samples per pixel
+  --noise X                  stop once the relative noise is below X
+
+animate options:
+",
+    camera_options!(),
+    "  --samples N                samples per pixel
+  --frames FIRST-LAST        render only these frames, counting from 0
+
+Shutter times are counted in frames. Frames are written to PREFIX0000.ppm,
+PREFIX0001.ppm and so on. Frames whose file exists are skipped, so an
+interrupted animation resumes where it stopped.
+
+encode options, writing an animated GIF, or an APNG for a .png or .apng OUTPUT:
+  --delay MILLISECONDS       how long each frame is shown (default 40)
+  --colors N                 size of the GIF palette, up to 256 (default 256)
+  --quantizer NAME           median-cut (default) or octree
+  --dither NAME              none (default) or floyd-steinberg",
+);
+
+type Result<T = ()> = std::result::Result<T, Box<dyn Error>>;
+
+/// Positional arguments and `--name value` options.
+struct Args<'a> {
+    positional: Vec<&'a str>,
+    options: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Args<'a> {
+    fn new(args: &'a [String]) -> Result<Self> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut args = args.iter().map(String::as_str);
+
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for --{name}"))?;
+                options.insert(name, value);
             } else {
-                world.add(Sphere::new(center, 0.2, DielectricMaterial::new(1.5).into()).into());
+                positional.push(arg);
             }
         }
+
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    fn option<T: FromStr>(&mut self, name: &str) -> Result<Option<T>>
+    where
+        T::Err: Error + 'static,
+    {
+        self.options
+            .remove(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|error| format!("invalid value for --{name}: {error}").into())
+            })
+            .transpose()
+    }
+
+    fn end(self) -> Result {
+        match self.options.keys().next() {
+            Some(name) => Err(format!("unknown option --{name}\n{USAGE}").into()),
+            None => Ok(()),
+        }
     }
+}
 
-    world.add(
-        Sphere::new(
-            Pos3::new(0., 1., 0.),
-            1.,
-            DielectricMaterial::new(1.5).into(),
+fn scene(name: &str) -> Result<Scene> {
+    Scene::from_name(name).ok_or_else(|| {
+        format!(
+            "unknown scene {name:?}, expected one of {}",
+            Scene::NAMES.join(", ")
         )
-        .into(),
-    );
+        .into()
+    })
+}
+
+/// The built-in scene with the options shared by every way of rendering.
+fn scene_with_options(name: &str, args: &mut Args) -> Result<Scene> {
+    let mut scene = scene(name)?;
+    camera_with_options(&mut scene.camera, args)?;
+
+    if let Some(target) = auto_focus_target(args)? {
+        auto_focus(&mut scene.camera, &scene.world.clone().into(), &target)?;
+    }
+
+    Ok(scene)
+}
+
+/// Where `--auto-focus` and `--focus-patch` ask to focus, if anywhere.
+fn auto_focus_target(args: &mut Args) -> Result<Option<AutoFocus>> {
+    let Some(pixel) = args.option::<String>("auto-focus")? else {
+        return Ok(None);
+    };
+
+    let pixel = match pixel.as_str() {
+        "center" => None,
+        pixel => {
+            let (x, y) = pixel
+                .split_once(',')
+                .ok_or("expected the focus pixel as X,Y or center")?;
+
+            Some((x.parse()?, y.parse()?))
+        }
+    };
+
+    Ok(Some(AutoFocus {
+        pixel,
+        patch_radius: args.option("focus-patch")?.unwrap_or(0),
+    }))
+}
+
+/// Focuses the camera on what `target` sees, and reports the distance.
+fn auto_focus(camera: &mut CameraOptions, world: &HittableObject, target: &AutoFocus) -> Result {
+    let distance = camera
+        .auto_focus(world, target)
+        .ok_or("auto-focus found nothing to focus on")?;
+
+    eprintln!("focusing at a distance of {distance:.4}");
+    Ok(())
+}
+
+/// Applies the options shared by every way of rendering.
+fn camera_with_options(camera: &mut CameraOptions, args: &mut Args) -> Result {
+    camera.seed = args.option("seed")?.or(camera.seed);
+    camera.image_width = args.option("width")?.unwrap_or(camera.image_width);
+    camera.image_height = args.option("height")?.or(camera.image_height);
+
+    let crop: Option<String> = args.option("crop")?;
+    let window: Option<String> = args.option("crop-window")?;
+
+    if crop.is_some() && window.is_some() {
+        return Err("--crop and --crop-window cannot be used together".into());
+    }
+
+    if let Some(crop) = crop {
+        let (origin, size) = crop
+            .rsplit_once(',')
+            .ok_or("expected the crop as X,Y,WIDTHxHEIGHT")?;
+        let (x, y) = origin
+            .split_once(',')
+            .ok_or("expected the crop as X,Y,WIDTHxHEIGHT")?;
+        let (width, height) = size
+            .split_once('x')
+            .ok_or("expected the crop as X,Y,WIDTHxHEIGHT")?;
+
+        camera.crop = Some(Crop::Pixels(Tile {
+            x: x.parse()?,
+            y: y.parse()?,
+            width: width.parse()?,
+            height: height.parse()?,
+        }));
+    }
+
+    if let Some(window) = window {
+        // Not padded, as a missing edge is more likely a typo than zero.
+        let [left, top, right, bottom] = numbers(&window, 0)?[..] else {
+            return Err("expected the crop window as LEFT,TOP,RIGHT,BOTTOM".into());
+        };
+
+        camera.crop = Some(Crop::Normalized {
+            left,
+            top,
+            right,
+            bottom,
+        });
+    }
 
-    world.add(
-        Sphere::new(
-            Pos3::new(-4., 1., 0.),
-            1.,
-            LambertianMaterial::new(Color::new(0.4, 0.2, 0.1)).into(),
+    let TileOptions { size, order } = camera.tiles;
+    let order = match args.option::<String>("tile-order")? {
+        Some(name) => TileOrder::from_name(&name).ok_or_else(|| {
+            format!(
+                "unknown tile order {name:?}, expected one of {}",
+                TileOrder::NAMES.join(", ")
+            )
+        })?,
+        None => order,
+    };
+
+    camera.tiles = TileOptions {
+        size: args.option("tile-size")?.unwrap_or(size),
+        order,
+    };
+
+    if let Some(blades) = args.option("aperture-blades")? {
+        camera.aperture.shape = ApertureShape::Polygon {
+            blades,
+            rotation: args.option("aperture-rotation")?.unwrap_or(0.),
+            curvature: args.option("aperture-curvature")?.unwrap_or(0.),
+        };
+    }
+
+    if let Some(mask) = args.option::<PathBuf>("aperture-mask")? {
+        let image: PPMImage = fs::read_to_string(mask)?.parse()?;
+        let mask = ApertureMask::new(&image).ok_or("the aperture mask is black")?;
+        camera.aperture.shape = ApertureShape::Mask(Arc::new(mask));
+    }
+
+    camera.aperture.cat_eye = args.option("cat-eye")?.unwrap_or(0.);
+
+    let shutter = &mut camera.shutter;
+    shutter.open = args.option("shutter-open")?.unwrap_or(shutter.open);
+    shutter.close = args.option("shutter-close")?.unwrap_or(shutter.close);
+    shutter.rolling_readout = args
+        .option("rolling-shutter")?
+        .unwrap_or(shutter.rolling_readout);
+
+    if let Some(name) = args.option::<String>("shutter-curve")? {
+        shutter.curve = ShutterCurve::from_name(&name).ok_or_else(|| {
+            format!(
+                "unknown shutter curve {name:?}, expected one of {}",
+                ShutterCurve::NAMES.join(", ")
+            )
+        })?;
+    }
+
+    if let Some(name) = args.option::<String>("projection")? {
+        camera.projection = Projection::from_name(&name).ok_or_else(|| {
+            format!(
+                "unknown projection {name:?}, expected one of {}",
+                Projection::NAMES.join(", ")
+            )
+        })?;
+    }
+
+    if let Some(fov) = args.option("fisheye-fov")? {
+        match &mut camera.projection {
+            Projection::Fisheye { fov: value, .. } => *value = fov,
+            _ => return Err("--fisheye-fov needs a fisheye projection".into()),
+        }
+    }
+
+    if let Some(size) = args.option::<String>("orthographic")? {
+        let (width, height) = size
+            .split_once('x')
+            .ok_or("expected the view plane size as WIDTHxHEIGHT")?;
+
+        camera.projection = Projection::Orthographic {
+            width: width.parse()?,
+            height: height.parse()?,
+        };
+    }
+
+    if let Some(matrix) = args.option::<String>("intrinsics")? {
+        let [fx, fy, cx, cy] = numbers(&matrix, 4)?[..] else {
+            return Err("expected the intrinsics as FX,FY,CX,CY".into());
+        };
+        let plate = args
+            .option::<String>("plate")?
+            .ok_or("--intrinsics needs the --plate size they were calibrated at")?;
+        let (width, height) = plate
+            .split_once('x')
+            .ok_or("expected the plate size as WIDTHxHEIGHT")?;
+
+        camera.intrinsics = Some(Intrinsics {
+            fx,
+            fy,
+            cx,
+            cy,
+            width: width.parse()?,
+            height: height.parse()?,
+        });
+    }
+
+    if let Some(coefficients) = args.option::<String>("brown-conrady")? {
+        let [k1, k2, k3, p1, p2] = numbers(&coefficients, 5)?[..] else {
+            return Err("expected at most five coefficients K1,K2,K3,P1,P2".into());
+        };
+
+        camera.distortion = Distortion::BrownConrady { k1, k2, k3, p1, p2 };
+    }
+
+    if let Some(coefficients) = args.option::<String>("division")? {
+        let [k1, k2] = numbers(&coefficients, 2)?[..] else {
+            return Err("expected at most two coefficients K1,K2".into());
+        };
+
+        camera.distortion = Distortion::Division { k1, k2 };
+    }
+
+    if let Some(name) = args.option::<String>("lens")? {
+        let system = match LensSystem::from_name(&name) {
+            Some(system) => system,
+            None => fs::read_to_string(&name)
+                .map_err(|error| {
+                    format!(
+                        "cannot read lens {name:?} ({error}), expected a file or one of {}",
+                        LensSystem::NAMES.join(", ")
+                    )
+                })?
+                .parse()?,
+        };
+
+        let system = match args.option("lens-stop")? {
+            Some(diameter) => system.stopped_down(diameter),
+            None => system,
+        };
+
+        let mut lens = RealisticLens::new(system);
+        lens.film_diagonal = args.option("film-diagonal")?.unwrap_or(lens.film_diagonal);
+        camera.lens = Lens::Realistic(lens);
+    }
+
+    if camera.image_height.is_some() && camera.projection.aspect_ratio().is_some() {
+        return Err("--height cannot be used with a projection of a fixed aspect ratio".into());
+    }
+
+    Ok(())
+}
+
+/// Comma-separated numbers, padded with zeros to at least `count` of them.
+fn numbers(list: &str, count: usize) -> Result<Vec<Fl>> {
+    let mut numbers = list
+        .split(',')
+        .map(|number| number.trim().parse())
+        .collect::<std::result::Result<Vec<Fl>, _>>()?;
+
+    if numbers.len() < count {
+        numbers.resize(count, 0.);
+    }
+
+    Ok(numbers)
+}
+
+fn render(name: &str, mut args: Args) -> Result {
+    let mut scene = scene_with_options(name, &mut args)?;
+    scene.camera.samples_per_pixel = args
+        .option("samples")?
+        .unwrap_or(scene.camera.samples_per_pixel);
+
+    let checkpoint: Option<String> = args.option("checkpoint")?;
+    let interval = Duration::from_secs_f64(args.option("interval")?.unwrap_or(60.));
+    let aovs: Option<PathBuf> = args.option("aovs")?;
+    let denoise = match args.option("denoise")? {
+        Some(strength) => Some(DenoiseOptions {
+            strength,
+            iterations: args
+                .option("denoise-iterations")?
+                .unwrap_or(DenoiseOptions::default().iterations),
+        }),
+        None => None,
+    };
+    args.end()?;
+
+    let camera = Camera::new(scene.camera);
+    let world = scene.world.into();
+
+    let (image, stats) = if aovs.is_some() || denoise.is_some() {
+        if checkpoint.is_some() {
+            return Err("--aovs and --denoise cannot be used with --checkpoint".into());
+        }
+
+        let (accumulator, buffers, stats) = camera.render_with_aovs(&world);
+        let beauty = accumulator.to_float_image();
+
+        if let Some(prefix) = aovs {
+            let mut path = prefix.clone().into_os_string();
+            path.push(".beauty.pfm");
+
+            beauty.save_pfm(Path::new(&path))?;
+            buffers.save(&prefix)?;
+        }
+
+        let image = match denoise {
+            Some(options) => denoise::denoise(&beauty, &buffers, &options).to_image(),
+            None => accumulator.to_image(),
+        };
+
+        (image, stats)
+    } else if let Some(checkpoint) = checkpoint {
+        let checkpoint = Path::new(&checkpoint);
+        let mut accumulator = load_or_new_accumulator(&camera, Some(checkpoint))?;
+
+        let stats = camera.render_checkpointed(&world, &mut accumulator, checkpoint, interval)?;
+        (accumulator.to_image(), stats)
+    } else {
+        camera.render_parallel_with_stats(&world)
+    };
+
+    eprintln!("{stats}");
+    println!("{image}");
+    Ok(())
+}
+
+/// Renders the frames of an animation to numbered files, skipping the ones
+/// which already exist.
+fn animate(name: &str, prefix: &str, mut args: Args) -> Result {
+    let mut animation = Animation::from_name(name).ok_or_else(|| {
+        format!(
+            "unknown animation {name:?}, expected one of {}",
+            Animation::NAMES.join(", ")
         )
-        .into(),
-    );
+    })?;
+
+    camera_with_options(&mut animation.camera, &mut args)?;
+
+    let focus_target = auto_focus_target(&mut args)?;
+    animation.camera.samples_per_pixel = args
+        .option("samples")?
+        .unwrap_or(animation.camera.samples_per_pixel);
+
+    let frames = match args.option::<String>("frames")? {
+        Some(range) => {
+            let (first, last) = range
+                .split_once('-')
+                .ok_or("expected the frames as FIRST-LAST")?;
+
+            first.parse()?..last.parse::<usize>()? + 1
+        }
+        None => 0..animation.timeline.frames,
+    };
+    args.end()?;
+
+    // Focus once for the whole animation, on the first frame rendered, with
+    // the camera and the shutter of that frame.
+    if let Some(target) = focus_target {
+        let mut scene = animation.scene(frames.start);
+        auto_focus(&mut scene.camera, &scene.world.into(), &target)?;
+        animation.camera.focus_dist = scene.camera.focus_dist;
+    }
+
+    let total = animation.timeline.frames;
+    let rendered = animation.render(
+        frames,
+        Path::new(prefix),
+        |frame, path, status| match status {
+            FrameStatus::Skipped => {
+                eprintln!("skipping frame {frame}, {} exists", path.display())
+            }
+            FrameStatus::Rendering => eprintln!("rendering frame {frame} of {total}"),
+        },
+    )?;
+    eprintln!("rendered {} frames", rendered.len());
+
+    Ok(())
+}
+
+/// Packs PPM frames into an animated GIF or APNG, chosen by the extension of
+/// the output.
+fn encode(output: &str, inputs: &[&str], mut args: Args) -> Result {
+    let defaults = GifOptions::default();
+    let delay = args
+        .option("delay")?
+        .map_or(defaults.delay, Duration::from_millis);
+
+    let quantizer = match args.option::<String>("quantizer")? {
+        Some(name) => Quantizer::from_name(&name).ok_or_else(|| {
+            format!(
+                "unknown quantizer {name:?}, expected one of {}",
+                Quantizer::NAMES.join(", ")
+            )
+        })?,
+        None => defaults.quantizer,
+    };
+
+    let dither = match args.option::<String>("dither")? {
+        Some(name) => Dither::from_name(&name).ok_or_else(|| {
+            format!(
+                "unknown dithering {name:?}, expected one of {}",
+                Dither::NAMES.join(", ")
+            )
+        })?,
+        None => defaults.dither,
+    };
+
+    let options = GifOptions {
+        delay,
+        colors: args.option("colors")?.unwrap_or(defaults.colors),
+        quantizer,
+        dither,
+    };
+    args.end()?;
+
+    let frames = inputs
+        .iter()
+        .map(|input| Ok(fs::read_to_string(input)?.parse()?))
+        .collect::<Result<Vec<PPMImage>>>()?;
+
+    let output = Path::new(output);
 
-    world.add(
-        Sphere::new(
-            Pos3::new(4., 1., 0.),
-            1.,
-            MetalMaterial::new(Color::new(0.7, 0.6, 0.5), 0.).into(),
+    match output.extension().and_then(|extension| extension.to_str()) {
+        Some("png" | "apng") => apng::save_apng(&frames, options.delay, output)?,
+        _ => gif::save_gif(&frames, &options, output)?,
+    }
+
+    Ok(())
+}
+
+fn load_or_new_accumulator(camera: &Camera, checkpoint: Option<&Path>) -> Result<Accumulator> {
+    let Some(checkpoint) = checkpoint.filter(|checkpoint| checkpoint.exists()) else {
+        return Ok(
+            Accumulator::new(camera.crop().width, camera.crop().height).with_seed(camera.seed())
+        );
+    };
+
+    let accumulator = Accumulator::load(checkpoint)?;
+
+    if (accumulator.width(), accumulator.height()) != (camera.crop().width, camera.crop().height) {
+        return Err("the checkpoint does not match the image size".into());
+    }
+
+    // Other seeds would repeat or mix up the samples of the checkpoint.
+    if accumulator.seed() != camera.seed() {
+        let seed =
+            |seed: Option<u64>| seed.map_or("no seed".to_owned(), |seed| format!("seed {seed}"));
+
+        return Err(format!(
+            "the checkpoint was rendered with {}, not {}",
+            seed(accumulator.seed()),
+            seed(camera.seed())
         )
-        .into(),
+        .into());
+    }
+
+    eprintln!(
+        "resuming from {} samples per pixel",
+        accumulator.min_samples()
+    );
+
+    Ok(accumulator)
+}
+
+fn progressive(name: &str, mut args: Args) -> Result {
+    let scene = scene_with_options(name, &mut args)?;
+
+    let defaults = ProgressiveOptions::default();
+    let seconds = |seconds: Option<f64>| seconds.map(Duration::from_secs_f64);
+
+    let options = ProgressiveOptions {
+        pass_samples: args
+            .option("pass-samples")?
+            .unwrap_or(defaults.pass_samples),
+        time_budget: seconds(args.option("budget")?),
+        target_samples: args.option("samples")?,
+        noise_threshold: args.option("noise")?,
+        output: args.option::<PathBuf>("output")?,
+        checkpoint: args.option::<PathBuf>("checkpoint")?,
+        write_interval: seconds(args.option("write-interval")?).unwrap_or(defaults.write_interval),
+    };
+    args.end()?;
+
+    let camera = Camera::new(scene.camera);
+    let mut accumulator = load_or_new_accumulator(&camera, options.checkpoint.as_deref())?;
+
+    let (reason, stats) =
+        camera.render_progressive(&scene.world.into(), &mut accumulator, &options)?;
+
+    eprintln!("{stats}");
+    eprintln!(
+        "stopped at {} samples per pixel because {reason}",
+        accumulator.min_samples()
     );
 
-    let camera = Camera::new(CameraOptions {
-        aspect_ratio: 16. / 9.,
-        image_width: 400,
-        samples_per_pixel: 100,
-        max_depth: 50,
-        v_fov: 20.,
-        look_from: Pos3::new(13., 2., 3.),
-        look_at: Pos3::new(0., 0., -0.),
-        vup: Vec3::new(0., 1., 0.),
-        defocus_angle: 0.6,
-        focus_dist: 10.,
-    });
-
-    let image = camera.render_parallel(&world.into());
+    println!("{}", accumulator.to_image());
+    Ok(())
+}
+
+fn merge_checkpoints(output: &str, inputs: &[&str]) -> Result {
+    let (first, rest) = inputs.split_first().ok_or(USAGE)?;
+    let mut accumulator = Accumulator::load(Path::new(first))?;
 
-    println!("{image}");
+    for input in rest {
+        accumulator.merge(&Accumulator::load(Path::new(input))?)?;
+    }
+
+    accumulator.save(Path::new(output))?;
+    println!("{}", accumulator.to_image());
+    Ok(())
+}
+
+/// Pastes a crop rendered with `--crop` back into a frame, with its top left
+/// corner at the given position, and writes the result to `output`.
+fn paste(output: &str, frame: &str, crop: &str, position: &str) -> Result {
+    let mut frame: PPMImage = fs::read_to_string(frame)?.parse()?;
+    let crop: PPMImage = fs::read_to_string(crop)?.parse()?;
+    let (x, y) = position
+        .split_once(',')
+        .ok_or("expected the position of the crop as X,Y")?;
+
+    frame.paste(&crop, x.parse()?, y.parse()?);
+    frame.save(Path::new(output))?;
+    Ok(())
+}
+
+/// Prints how far a PPM image is from a reference, and optionally writes a
+/// heatmap of the relative error, white at 100%.
+fn compare(image: &str, reference: &str, heatmap: Option<&str>) -> Result {
+    let image: PPMImage = fs::read_to_string(image)?.parse()?;
+    let reference: PPMImage = fs::read_to_string(reference)?.parse()?;
+
+    if (image.width(), image.height()) != (reference.width(), reference.height()) {
+        return Err("the images differ in size".into());
+    }
+
+    let (image, reference) = (FloatImage::from(&image), FloatImage::from(&reference));
+    println!("{}", metrics::Comparison::new(&image, &reference));
+
+    if let Some(heatmap) = heatmap {
+        metrics::heatmap(&metrics::relative_error(&image, &reference), 1.)
+            .save(Path::new(heatmap))?;
+    }
+
+    Ok(())
+}
+
+/// Prints the energy lost by each material in a white furnace.
+fn furnace(samples: Option<&str>) -> Result {
+    let samples = samples.map_or(Ok(256), str::parse)?;
+
+    for result in furnace::report(samples) {
+        println!("{result}");
+    }
+
+    Ok(())
+}
+
+fn fish_export(name: &str) -> Result {
+    print!("{}", fish::encode_scene(&scene(name)?)?);
+    Ok(())
+}
+
+fn fish_parity(
+    scene_file: &str,
+    random_log: &str,
+    fish_ppm: &str,
+    tolerance: Option<&str>,
+) -> Result {
+    let scene = fish::decode_scene(&fs::read_to_string(scene_file)?)?;
+    let log: fish::RandomLog = fs::read_to_string(random_log)?.parse()?;
+    let reference: PPMImage = fs::read_to_string(fish_ppm)?.parse()?;
+    let tolerance = tolerance.map_or(Ok(fish::DEFAULT_TOLERANCE), str::parse)?;
+
+    let camera = Camera::new(scene.camera);
+    let render = fish::render_recorded(&camera, &scene.world.into(), &log);
+    let report = fish::compare(&render.image, &reference, tolerance)?;
+
+    eprintln!("{report}");
+
+    if !render.desynced.is_empty() {
+        eprintln!(
+            "{} pixels did not use exactly the recorded random numbers",
+            render.desynced.len()
+        );
+    }
+
+    for &(x, y) in &report.mismatched {
+        let expected_mismatch = render.desynced.contains(&(x, y));
+        eprintln!(
+            "  ({x}, {y}): rust {} fish {}{}",
+            render.image[(x, y)],
+            reference[(x, y)],
+            if expected_mismatch { " (desynced)" } else { "" },
+        );
+    }
+
+    if report.is_match() {
+        Ok(())
+    } else {
+        Err("the renderers disagree".into())
+    }
+}
+
+fn run(args: &[String]) -> Result {
+    let args = Args::new(args)?;
+
+    match args.positional[..] {
+        [] | ["render"] => render("book-cover", args),
+        ["render", name] => render(name, args),
+        ["progressive", name] => progressive(name, args),
+        ["animate", name, prefix] => animate(name, prefix, args),
+        ["encode", output, ref inputs @ ..] if !inputs.is_empty() => {
+            let inputs = inputs.to_vec();
+            encode(output, &inputs, args)
+        }
+        ["merge-checkpoints", output, ref inputs @ ..] => {
+            let inputs = inputs.to_vec();
+            args.end()?;
+            merge_checkpoints(output, &inputs)
+        }
+        ["paste", output, frame, crop, position] => {
+            args.end()?;
+            paste(output, frame, crop, position)
+        }
+        ["compare", image, reference] => {
+            args.end()?;
+            compare(image, reference, None)
+        }
+        ["compare", image, reference, heatmap] => {
+            args.end()?;
+            compare(image, reference, Some(heatmap))
+        }
+        ["furnace"] => {
+            args.end()?;
+            furnace(None)
+        }
+        ["furnace", samples] => {
+            args.end()?;
+            furnace(Some(samples))
+        }
+        ["fish-export", name] => {
+            args.end()?;
+            fish_export(name)
+        }
+        ["fish-parity", scene_file, random_log, fish_ppm] => {
+            args.end()?;
+            fish_parity(scene_file, random_log, fish_ppm, None)
+        }
+        ["fish-parity", scene_file, random_log, fish_ppm, tolerance] => {
+            args.end()?;
+            fish_parity(scene_file, random_log, fish_ppm, Some(tolerance))
+        }
+        _ => Err(USAGE.into()),
+    }
+}
+
+pub fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
 }
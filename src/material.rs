@@ -1,17 +1,28 @@
+//! Materials, which decide how rays scatter off a surface.
+
 use std::fmt::Debug;
 
 use crate::{
-    dielectric::DielectricMaterial, hittable::HitRecord, lambertian::LambertianMaterial,
-    metal::MetalMaterial, ray::Ray, vec3::Color,
+    conductor::ConductorMaterial, dielectric::DielectricMaterial, hittable::HitRecord,
+    lambertian::LambertianMaterial, metal::MetalMaterial, ray::Ray,
+    rough_dielectric::RoughDielectricMaterial, vec3::Color,
 };
 
+/// A scattered ray and how much of its color reaches the incoming ray.
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct ScatterRecord {
     pub attenuation: Color,
     pub scattered: Ray,
 }
 
+/// How rays interact with a surface.
 pub trait Material: Debug + Send + Sync {
+    /// The color of the surface, written to the albedo AOV.
+    fn albedo(&self) -> Color {
+        Color::new(1., 1., 1.)
+    }
+
+    /// Returns the scattered ray, or `None` if the ray is absorbed.
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         let _ = r_in;
         let _ = rec;
@@ -20,11 +31,20 @@ pub trait Material: Debug + Send + Sync {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Any of the materials.
+#[derive(Debug, PartialEq, Clone)]
 pub enum MaterialObject {
+    Conductor(ConductorMaterial),
     Dielectric(DielectricMaterial),
     Lambertian(LambertianMaterial),
     Metal(MetalMaterial),
+    RoughDielectric(RoughDielectricMaterial),
+}
+
+impl From<ConductorMaterial> for MaterialObject {
+    fn from(value: ConductorMaterial) -> Self {
+        MaterialObject::Conductor(value)
+    }
 }
 
 impl From<DielectricMaterial> for MaterialObject {
@@ -45,12 +65,30 @@ impl From<MetalMaterial> for MaterialObject {
     }
 }
 
+impl From<RoughDielectricMaterial> for MaterialObject {
+    fn from(value: RoughDielectricMaterial) -> Self {
+        MaterialObject::RoughDielectric(value)
+    }
+}
+
 impl Material for MaterialObject {
+    fn albedo(&self) -> Color {
+        match self {
+            MaterialObject::Conductor(i) => i.albedo(),
+            MaterialObject::Dielectric(i) => i.albedo(),
+            MaterialObject::Lambertian(i) => i.albedo(),
+            MaterialObject::Metal(i) => i.albedo(),
+            MaterialObject::RoughDielectric(i) => i.albedo(),
+        }
+    }
+
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         match self {
+            MaterialObject::Conductor(i) => i.scatter(r_in, rec),
             MaterialObject::Dielectric(i) => i.scatter(r_in, rec),
             MaterialObject::Lambertian(i) => i.scatter(r_in, rec),
             MaterialObject::Metal(i) => i.scatter(r_in, rec),
+            MaterialObject::RoughDielectric(i) => i.scatter(r_in, rec),
         }
     }
 }
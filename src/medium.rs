@@ -0,0 +1,203 @@
+//! The insides of objects, which absorb light along the rays crossing them
+//! and decide how light bends between nested objects.
+
+use crate::{float::Fl, vec3::Color};
+
+/// Most objects a ray can be inside at once. Entering more is ignored, as if
+/// the innermost objects were not there.
+const MAX_NESTING: usize = 4;
+
+/// What a ray travels through between two surfaces.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Medium {
+    refraction_index: Fl,
+    /// Fraction of each channel absorbed per unit of distance.
+    absorption: Color,
+    /// Where objects overlap, the medium of highest priority fills the
+    /// overlap.
+    priority: u32,
+}
+
+impl Medium {
+    /// Empty space, which absorbs nothing. This is the default.
+    pub const VACUUM: Self = Self {
+        refraction_index: 1.,
+        absorption: Color::new(0., 0., 0.),
+        priority: 0,
+    };
+
+    pub const fn new(refraction_index: Fl, absorption: Color, priority: u32) -> Self {
+        Self {
+            refraction_index,
+            absorption,
+            priority,
+        }
+    }
+
+    pub const fn refraction_index(&self) -> Fl {
+        self.refraction_index
+    }
+
+    pub const fn absorption(&self) -> Color {
+        self.absorption
+    }
+
+    pub const fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    pub fn is_clear(&self) -> bool {
+        self.absorption == Color::default()
+    }
+
+    /// Fraction of each channel left after crossing `distance` of the
+    /// medium, following the Beer-Lambert law.
+    pub fn transmittance(&self, distance: Fl) -> Color {
+        let channel = |absorption: Fl| (-absorption * distance).exp();
+
+        Color::new(
+            channel(self.absorption.x()),
+            channel(self.absorption.y()),
+            channel(self.absorption.z()),
+        )
+    }
+}
+
+/// A medium entered by a ray, and the id of its object, in single precision
+/// to keep rays small.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+struct Entry {
+    object_id: u32,
+    priority: u32,
+    refraction_index: f32,
+    absorption: [f32; 3],
+}
+
+impl Entry {
+    const NONE: Self = Self {
+        object_id: 0,
+        priority: 0,
+        refraction_index: 1.,
+        absorption: [0.; 3],
+    };
+
+    fn new(object_id: usize, medium: Medium) -> Self {
+        let a = medium.absorption;
+
+        Self {
+            object_id: object_id as u32,
+            priority: medium.priority,
+            refraction_index: medium.refraction_index as f32,
+            absorption: [a.x() as f32, a.y() as f32, a.z() as f32],
+        }
+    }
+
+    fn medium(&self) -> Medium {
+        let [r, g, b] = self.absorption.map(Fl::from);
+
+        Medium::new(
+            self.refraction_index.into(),
+            Color::new(r, g, b),
+            self.priority,
+        )
+    }
+}
+
+/// The media of the objects a ray is inside, in the order it entered them.
+/// It is small enough to copy with the ray.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MediumStack {
+    entries: [Entry; MAX_NESTING],
+    len: u32,
+}
+
+impl Default for MediumStack {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl MediumStack {
+    /// Outside of every object.
+    pub const EMPTY: Self = Self {
+        entries: [Entry::NONE; MAX_NESTING],
+        len: 0,
+    };
+
+    fn entries(&self) -> &[Entry] {
+        &self.entries[..self.len as usize]
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the ray is inside the object `object_id`.
+    pub fn contains(&self, object_id: usize) -> bool {
+        self.entries()
+            .iter()
+            .any(|entry| entry.object_id as usize == object_id)
+    }
+
+    /// The medium the ray travels through: the one of highest priority, or
+    /// the last one entered among equal priorities. Empty space outside of
+    /// every object.
+    pub fn current(&self) -> Medium {
+        Self::highest(self.entries())
+    }
+
+    /// The medium around the object `object_id`, which the ray would travel
+    /// through outside of it.
+    pub fn around(&self, object_id: usize) -> Medium {
+        Self::highest(self.left(object_id).entries())
+    }
+
+    fn highest(entries: &[Entry]) -> Medium {
+        entries
+            .iter()
+            .reduce(|current, entry| {
+                if current.priority > entry.priority {
+                    current
+                } else {
+                    entry
+                }
+            })
+            .map_or(Medium::VACUUM, Entry::medium)
+    }
+
+    /// The stack once inside the object `object_id`, made of `medium`.
+    pub fn entered(&self, object_id: usize, medium: Medium) -> Self {
+        let mut stack = *self;
+
+        if (stack.len as usize) < MAX_NESTING && !self.contains(object_id) {
+            stack.entries[stack.len as usize] = Entry::new(object_id, medium);
+            stack.len += 1;
+        }
+
+        stack
+    }
+
+    /// The stack once outside of the object `object_id`.
+    pub fn left(&self, object_id: usize) -> Self {
+        let mut stack = Self::EMPTY;
+
+        for &entry in self.entries() {
+            if entry.object_id as usize != object_id {
+                stack.entries[stack.len as usize] = entry;
+                stack.len += 1;
+            }
+        }
+
+        stack
+    }
+
+    /// The stack once through the surface of the object `object_id`, made
+    /// of `medium`, into it or out of it.
+    pub fn crossed(&self, object_id: usize, medium: Medium, entering: bool) -> Self {
+        if entering {
+            self.entered(object_id, medium)
+        } else {
+            self.left(object_id)
+        }
+    }
+}
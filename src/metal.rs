@@ -1,3 +1,5 @@
+//! Reflective surfaces.
+
 use crate::{
     float::Fl,
     hittable::HitRecord,
@@ -6,6 +8,7 @@ use crate::{
     vec3::{Color, Vec3},
 };
 
+/// A mirror, blurred by perturbing the reflected ray up to `fuzz`.
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct MetalMaterial {
     albedo: Color,
@@ -16,14 +19,27 @@ impl MetalMaterial {
     pub fn new(albedo: Color, fuzz: Fl) -> Self {
         Self { albedo, fuzz }
     }
+
+    pub const fn albedo(&self) -> Color {
+        self.albedo
+    }
+
+    pub const fn fuzz(&self) -> Fl {
+        self.fuzz
+    }
 }
 
 impl Material for MetalMaterial {
+    fn albedo(&self) -> Color {
+        self.albedo
+    }
+
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         let reflected =
             r_in.direction().reflect(&rec.normal) + Vec3::random_normalized() * self.fuzz;
         let scattered = Ray::new(rec.p, reflected)
             .at_time_of(r_in)
+            .in_media_of(r_in)
             .for_pixel_of(r_in);
 
         (scattered.direction().dot(&rec.normal) > 0.).then_some(ScatterRecord {
@@ -0,0 +1,205 @@
+//! Measures of how much two images differ, to catch changes in renders.
+//!
+//! Every function takes two non-empty images of the same size and channel
+//! count, and panics otherwise. Values are compared as they are, so 8-bit
+//! images should be converted with [`FloatImage::from`] first, which maps them
+//! to `0..=1`.
+
+use std::fmt::Display;
+
+use crate::{
+    float::{lerp, Fl},
+    float_image::FloatImage,
+    ppm::{PPMColor, PPMImage},
+    vec3::Color,
+};
+
+/// Side of the square windows over which SSIM is computed.
+const SSIM_WINDOW: usize = 7;
+
+/// Relative errors are measured against at least this much, so that tiny
+/// differences in black pixels do not dominate.
+const MIN_RELATIVE: Fl = 0.01;
+
+fn assert_same_size(a: &FloatImage, b: &FloatImage) {
+    assert_eq!(
+        (a.width(), a.height(), a.channels()),
+        (b.width(), b.height(), b.channels()),
+        "the images differ in size",
+    );
+    assert!(!a.data().is_empty(), "the images are empty");
+}
+
+/// Mean squared error over every channel of every pixel.
+pub fn mse(a: &FloatImage, b: &FloatImage) -> Fl {
+    assert_same_size(a, b);
+
+    let sum: Fl = a
+        .data()
+        .iter()
+        .zip(b.data())
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum();
+
+    sum / a.data().len() as Fl
+}
+
+/// Root mean squared error, in the units of the images.
+pub fn rmse(a: &FloatImage, b: &FloatImage) -> Fl {
+    mse(a, b).sqrt()
+}
+
+/// Peak signal-to-noise ratio in decibels, for images whose values peak at
+/// one. Identical images have an infinite PSNR.
+pub fn psnr(a: &FloatImage, b: &FloatImage) -> Fl {
+    -10. * mse(a, b).log10()
+}
+
+/// Mean structural similarity of the luminance of the images over sliding
+/// 7x7 windows, between -1 and 1, and 1 for identical images. Unlike the
+/// MSE, it follows how different the images look rather than how far apart
+/// their values are.
+pub fn ssim(a: &FloatImage, b: &FloatImage) -> Fl {
+    assert_same_size(a, b);
+
+    // Constants of Wang et al. for a dynamic range of one.
+    const C1: Fl = 0.01 * 0.01;
+    const C2: Fl = 0.03 * 0.03;
+
+    let luminance = |image: &FloatImage, x, y| -> Fl {
+        let pixel = image.get(x, y);
+
+        match pixel.len() {
+            3 => Color::new(pixel[0], pixel[1], pixel[2]).luminance(),
+            _ => pixel.iter().sum::<Fl>() / pixel.len() as Fl,
+        }
+    };
+
+    let window_width = SSIM_WINDOW.min(a.width());
+    let window_height = SSIM_WINDOW.min(a.height());
+
+    let mut sum = 0.;
+    let mut windows = 0;
+
+    for top in 0..=a.height() - window_height {
+        for left in 0..=a.width() - window_width {
+            let pixels = (top..top + window_height)
+                .flat_map(|y| (left..left + window_width).map(move |x| (x, y)));
+            let n = (window_width * window_height) as Fl;
+
+            let (mut mean_a, mut mean_b) = (0., 0.);
+            let (mut square_a, mut square_b, mut product) = (0., 0., 0.);
+
+            for (x, y) in pixels {
+                let (la, lb) = (luminance(a, x, y), luminance(b, x, y));
+                mean_a += la;
+                mean_b += lb;
+                square_a += la * la;
+                square_b += lb * lb;
+                product += la * lb;
+            }
+
+            mean_a /= n;
+            mean_b /= n;
+            let variance_a = square_a / n - mean_a * mean_a;
+            let variance_b = square_b / n - mean_b * mean_b;
+            let covariance = product / n - mean_a * mean_b;
+
+            sum += ((2. * mean_a * mean_b + C1) * (2. * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (variance_a + variance_b + C2));
+            windows += 1;
+        }
+    }
+
+    sum / windows as Fl
+}
+
+/// The error of `a` relative to `reference` at every pixel, averaged over
+/// the channels, as a one-channel image.
+pub fn relative_error(a: &FloatImage, reference: &FloatImage) -> FloatImage {
+    assert_same_size(a, reference);
+
+    let mut errors = FloatImage::new(a.width(), a.height(), 1);
+
+    for y in 0..a.height() {
+        for x in 0..a.width() {
+            let pixel = a.get(x, y);
+            let expected = reference.get(x, y);
+
+            errors.get_mut(x, y)[0] = pixel
+                .iter()
+                .zip(expected)
+                .map(|(value, expected)| {
+                    (value - expected).abs() / expected.abs().max(MIN_RELATIVE)
+                })
+                .sum::<Fl>()
+                / pixel.len() as Fl;
+        }
+    }
+
+    errors
+}
+
+/// Colors the first channel of `errors` from black at zero, through blue,
+/// red and yellow, to white at `max` and above.
+pub fn heatmap(errors: &FloatImage, max: Fl) -> PPMImage {
+    const STOPS: [(u8, u8, u8); 5] = [
+        (0, 0, 0),
+        (0, 0, 255),
+        (255, 0, 0),
+        (255, 255, 0),
+        (255, 255, 255),
+    ];
+
+    let pixels = (0..errors.height())
+        .flat_map(|y| (0..errors.width()).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let t = (errors.get(x, y)[0] / max).clamp(0., 1.) * (STOPS.len() - 1) as Fl;
+            let i = (t.floor() as usize).min(STOPS.len() - 2);
+            let (from, to) = (STOPS[i], STOPS[i + 1]);
+            let mix =
+                |from: u8, to: u8| lerp(t - i as Fl, Fl::from(from), Fl::from(to)).round() as u8;
+
+            PPMColor::new(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+        })
+        .collect();
+
+    PPMImage::new_with(errors.width(), errors.height(), pixels)
+}
+
+/// Every metric between an image and a reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub mse: Fl,
+    pub rmse: Fl,
+    pub psnr: Fl,
+    pub ssim: Fl,
+    /// Mean of the [`relative_error`] of every pixel.
+    pub mean_relative_error: Fl,
+}
+
+impl Comparison {
+    pub fn new(image: &FloatImage, reference: &FloatImage) -> Self {
+        let mse = mse(image, reference);
+        let errors = relative_error(image, reference);
+
+        Self {
+            mse,
+            rmse: mse.sqrt(),
+            psnr: -10. * mse.log10(),
+            ssim: ssim(image, reference),
+            mean_relative_error: errors.data().iter().sum::<Fl>()
+                / errors.data().len().max(1) as Fl,
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MSE {:.6}, RMSE {:.4}, PSNR {:.2} dB, SSIM {:.4}, mean relative error {:.4}",
+            self.mse, self.rmse, self.psnr, self.ssim, self.mean_relative_error
+        )
+    }
+}
@@ -0,0 +1,152 @@
+//! The GGX (Trowbridge-Reitz) distribution of microfacets, which models a
+//! rough surface as many tiny mirrors, for physically based rough materials.
+//!
+//! Directions are in a local frame where the surface normal is `+z`, and
+//! both directions point away from the surface.
+
+use std::f64::consts::PI;
+
+use crate::{float::Fl, vec3::Vec3};
+
+/// Smallest roughness, below which the distribution is too peaked to sample
+/// and evaluate reliably. Such surfaces are mirrors for all purposes.
+const MIN_ALPHA: Fl = 1e-4;
+
+/// An orthonormal frame around a surface normal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TangentFrame {
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
+    pub normal: Vec3,
+}
+
+impl TangentFrame {
+    /// A frame around the unit `normal`, whose tangent runs along lines of
+    /// latitude around the world `y` axis, as the grain of a turned sphere.
+    pub fn new(normal: Vec3) -> Self {
+        let up = Vec3::new(0., 1., 0.);
+        let tangent = up.cross(&normal);
+
+        // At the poles, any direction is a line of latitude.
+        let tangent = if tangent.squared_abs() < 1e-12 {
+            Vec3::new(1., 0., 0.).cross(&normal)
+        } else {
+            tangent
+        }
+        .normalize();
+
+        Self {
+            tangent,
+            bitangent: normal.cross(&tangent),
+            normal,
+        }
+    }
+
+    pub fn to_local(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            v.dot(&self.tangent),
+            v.dot(&self.bitangent),
+            v.dot(&self.normal),
+        )
+    }
+
+    pub fn to_world(&self, v: Vec3) -> Vec3 {
+        self.tangent * v.x() + self.bitangent * v.y() + self.normal * v.z()
+    }
+}
+
+/// A GGX distribution, stretched by `alpha_x` along the tangent and
+/// `alpha_y` along the bitangent. Equal roughnesses make it isotropic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ggx {
+    alpha_x: Fl,
+    alpha_y: Fl,
+}
+
+impl Ggx {
+    pub fn new(alpha_x: Fl, alpha_y: Fl) -> Self {
+        Self {
+            alpha_x: alpha_x.max(MIN_ALPHA),
+            alpha_y: alpha_y.max(MIN_ALPHA),
+        }
+    }
+
+    /// Density of microfacet normals `m`, per unit of projected area.
+    pub fn d(&self, m: Vec3) -> Fl {
+        if m.z() <= 0. {
+            return 0.;
+        }
+
+        let x = m.x() / self.alpha_x;
+        let y = m.y() / self.alpha_y;
+        let e = x * x + y * y + m.z() * m.z();
+
+        1. / (PI * self.alpha_x * self.alpha_y * e * e)
+    }
+
+    /// Smith's auxiliary function, the ratio of hidden to visible projected
+    /// area of microfacets seen from `w`.
+    pub fn lambda(&self, w: Vec3) -> Fl {
+        let cos2 = w.z() * w.z();
+
+        if cos2 == 0. {
+            return Fl::INFINITY;
+        }
+
+        let x = self.alpha_x * w.x();
+        let y = self.alpha_y * w.y();
+        let tan2_alpha2 = (x * x + y * y) / cos2;
+
+        ((1. + tan2_alpha2).sqrt() - 1.) / 2.
+    }
+
+    /// Fraction of microfacets visible from `w`.
+    pub fn g1(&self, w: Vec3) -> Fl {
+        1. / (1. + self.lambda(w))
+    }
+
+    /// Fraction of microfacets visible from both `wo` and `wi`, with the
+    /// height-correlated masking and shadowing of Smith.
+    pub fn g2(&self, wo: Vec3, wi: Vec3) -> Fl {
+        1. / (1. + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// A microfacet normal seen from `w`, which must be above the surface,
+    /// sampled in proportion to its visible area from the uniform numbers
+    /// `u1` and `u2`, following Heitz's "Sampling the GGX Distribution of
+    /// Visible Normals".
+    pub fn sample_visible_normal(&self, w: Vec3, u1: Fl, u2: Fl) -> Vec3 {
+        // Stretch to the hemisphere configuration, where the distribution is
+        // a half sphere.
+        let wh = Vec3::new(self.alpha_x * w.x(), self.alpha_y * w.y(), w.z()).normalize();
+
+        let length2 = wh.x() * wh.x() + wh.y() * wh.y();
+        let t1 = if length2 > 0. {
+            Vec3::new(-wh.y(), wh.x(), 0.) / length2.sqrt()
+        } else {
+            Vec3::new(1., 0., 0.)
+        };
+        let t2 = wh.cross(&t1);
+
+        // A point on the disk, warped to the part of it which is visible.
+        let r = u1.sqrt();
+        let phi = 2. * PI * u2;
+        let p1 = r * phi.cos();
+        let p2 = r * phi.sin();
+        let s = (1. + wh.z()) / 2.;
+        let p2 = (1. - s) * (1. - p1 * p1).sqrt() + s * p2;
+
+        let nh = t1 * p1 + t2 * p2 + wh * (1. - p1 * p1 - p2 * p2).max(0.).sqrt();
+
+        Vec3::new(self.alpha_x * nh.x(), self.alpha_y * nh.y(), nh.z().max(0.)).normalize()
+    }
+
+    /// Density of [`Ggx::sample_visible_normal`] sampling `m` from `w`.
+    pub fn visible_normal_pdf(&self, w: Vec3, m: Vec3) -> Fl {
+        if w.z() <= 0. {
+            return 0.;
+        }
+
+        self.g1(w) * w.dot(&m).max(0.) * self.d(m) / w.z()
+    }
+}
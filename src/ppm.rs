@@ -1,8 +1,17 @@
+//! In-memory 8-bit RGB images, printed in the plain PPM (P3) format.
+
 use std::{
+    error::Error,
     fmt::Display,
+    io::{self, Write},
     ops::{Index, IndexMut},
+    path::Path,
+    str::FromStr,
 };
 
+use crate::file;
+
+/// An 8-bit RGB color.
 #[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct PPMColor {
     r: u8,
@@ -14,6 +23,18 @@ impl PPMColor {
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
+
+    pub const fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub const fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub const fn b(&self) -> u8 {
+        self.b
+    }
 }
 
 impl Display for PPMColor {
@@ -22,6 +43,7 @@ impl Display for PPMColor {
     }
 }
 
+/// A row-major image. Its [`Display`] implementation prints it as a PPM file.
 #[derive(Debug, Clone)]
 pub struct PPMImage {
     width: usize,
@@ -38,6 +60,7 @@ impl PPMImage {
         }
     }
 
+    /// Wraps the given row-major pixels, which must be `width * height` long.
     pub fn new_with(width: usize, height: usize, pixels: Vec<PPMColor>) -> Self {
         Self {
             width,
@@ -53,6 +76,27 @@ impl PPMImage {
     pub const fn height(&self) -> usize {
         self.height
     }
+
+    pub fn pixels(&self) -> &[PPMColor] {
+        &self.pixels
+    }
+
+    /// Copies `image` over this one with its top left corner at `(x, y)`,
+    /// such as a cropped render back into the full one. Pixels falling
+    /// outside of this image are left out.
+    pub fn paste(&mut self, image: &PPMImage, x: usize, y: usize) {
+        for row in 0..image.height.min(self.height.saturating_sub(y)) {
+            for column in 0..image.width.min(self.width.saturating_sub(x)) {
+                self[(x + column, y + row)] = image[(column, row)];
+            }
+        }
+    }
+
+    /// Writes the image to a PPM file. The file is replaced at once, so
+    /// viewers never see a partially written image.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        file::replace(path, |writer| write!(writer, "{self}"))
+    }
 }
 
 impl Index<usize> for PPMImage {
@@ -96,3 +140,106 @@ impl Display for PPMImage {
         Ok(())
     }
 }
+
+/// The size shared by the frames of an animation, which must not be empty.
+pub(crate) fn frame_size(frames: &[PPMImage]) -> io::Result<(u32, u32)> {
+    let first = frames
+        .first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no frames to encode"))?;
+
+    if frames
+        .iter()
+        .any(|frame| (frame.width(), frame.height()) != (first.width(), first.height()))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the frames differ in size",
+        ));
+    }
+
+    match (u32::try_from(first.width()), u32::try_from(first.height())) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the frames are empty or too large",
+        )),
+    }
+}
+
+/// Why a PPM file could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PPMParseError {
+    /// The file does not start with `P3`.
+    UnsupportedFormat,
+    /// The file ended before every pixel was read.
+    UnexpectedEnd,
+    /// A value is not a number, or is out of range.
+    InvalidValue(String),
+}
+
+impl Display for PPMParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PPMParseError::UnsupportedFormat => {
+                write!(f, "only plain PPM (P3) files are supported")
+            }
+            PPMParseError::UnexpectedEnd => write!(f, "unexpected end of file"),
+            PPMParseError::InvalidValue(value) => write!(f, "invalid value {value:?}"),
+        }
+    }
+}
+
+impl Error for PPMParseError {}
+
+/// Reads a plain PPM file, scaling its values to a maximum of 255.
+impl FromStr for PPMImage {
+    type Err = PPMParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s
+            .lines()
+            .map(|line| line.split_once('#').map_or(line, |(line, _comment)| line))
+            .flat_map(str::split_whitespace);
+
+        if tokens.next() != Some("P3") {
+            return Err(PPMParseError::UnsupportedFormat);
+        }
+
+        let mut next_number = || -> Result<usize, PPMParseError> {
+            let token = tokens.next().ok_or(PPMParseError::UnexpectedEnd)?;
+            token
+                .parse()
+                .map_err(|_| PPMParseError::InvalidValue(token.to_owned()))
+        };
+
+        let width = next_number()?;
+        let height = next_number()?;
+        let max_value = next_number()?;
+
+        if max_value == 0 || max_value > 65535 {
+            return Err(PPMParseError::InvalidValue(max_value.to_string()));
+        }
+
+        let mut next_channel = || -> Result<u8, PPMParseError> {
+            let value = next_number()?;
+
+            if value > max_value {
+                return Err(PPMParseError::InvalidValue(value.to_string()));
+            }
+
+            Ok((value * 255 / max_value) as u8)
+        };
+
+        let pixels = (0..width * height)
+            .map(|_| {
+                Ok(PPMColor::new(
+                    next_channel()?,
+                    next_channel()?,
+                    next_channel()?,
+                ))
+            })
+            .collect::<Result<_, PPMParseError>>()?;
+
+        Ok(Self::new_with(width, height, pixels))
+    }
+}
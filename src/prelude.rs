@@ -0,0 +1,31 @@
+//! Everything needed to build a scene and render it.
+//!
+//! ```
+//! use path_tracing::prelude::*;
+//! ```
+
+pub use crate::{
+    accumulator::Accumulator,
+    animation::{AnimatedMaterial, AnimatedSphere, Animation, Timeline},
+    camera::{AutoFocus, Background, Camera, CameraMotion, CameraOptions},
+    conductor::ConductorMaterial,
+    crop::Crop,
+    dielectric::DielectricMaterial,
+    distortion::{Distortion, Intrinsics},
+    float::Fl,
+    hittable::{Hittable, HittableObject},
+    hittable_list::HittableList,
+    interval::Interval,
+    keyframe::{Interpolation, Track},
+    lambertian::LambertianMaterial,
+    lens::{Lens, LensSystem, RealisticLens},
+    material::{Material, MaterialObject},
+    metal::MetalMaterial,
+    ppm::{PPMColor, PPMImage},
+    projection::{FisheyeModel, Projection},
+    ray::Ray,
+    rough_dielectric::RoughDielectricMaterial,
+    scene::Scene,
+    sphere::Sphere,
+    vec3::{Color, Pos3, Vec3},
+};
@@ -0,0 +1,63 @@
+//! Options for rendering in passes until a budget is spent.
+
+use std::{fmt::Display, path::PathBuf, time::Duration};
+
+use crate::float::Fl;
+
+/// Options for [`Camera::render_progressive`](crate::Camera::render_progressive).
+///
+/// Every pass adds `pass_samples` samples to the whole image. The render stops
+/// after the first pass which reaches any of the limits. Without limits, it
+/// only stops when it is killed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressiveOptions {
+    /// Samples added to every pixel in each pass.
+    pub pass_samples: usize,
+    /// Wall-clock time to spend. A pass which is expected to end after the
+    /// budget is not started.
+    pub time_budget: Option<Duration>,
+    /// Samples per pixel to reach.
+    pub target_samples: Option<usize>,
+    /// Noise, as measured by [`Accumulator::noise`](crate::Accumulator::noise),
+    /// to get below.
+    pub noise_threshold: Option<Fl>,
+    /// Where to write the image so far.
+    pub output: Option<PathBuf>,
+    /// Where to save the accumulator so far, to resume later.
+    pub checkpoint: Option<PathBuf>,
+    /// Time between two writes of the output and checkpoint. They are always
+    /// written at the end.
+    pub write_interval: Duration,
+}
+
+impl Default for ProgressiveOptions {
+    fn default() -> Self {
+        Self {
+            pass_samples: 4,
+            time_budget: None,
+            target_samples: None,
+            noise_threshold: None,
+            output: None,
+            checkpoint: None,
+            write_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Which limit ended a progressive render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    TimeBudget,
+    TargetSamples,
+    NoiseThreshold,
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopReason::TimeBudget => write!(f, "the time budget is spent"),
+            StopReason::TargetSamples => write!(f, "the target sample count is reached"),
+            StopReason::NoiseThreshold => write!(f, "the noise is below the threshold"),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! How the camera maps pixels to rays.
+
+use std::f64::consts::PI;
+
+use crate::{float::Fl, vec3::Vec3};
+
+/// How a fisheye lens maps angles from the view direction to distances from
+/// the center of the image circle.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FisheyeModel {
+    /// Distance proportional to the angle, so angles can be measured on the
+    /// image.
+    #[default]
+    Equidistant,
+    /// Distance proportional to `sin(angle / 2)`, so every pixel covers the
+    /// same solid angle.
+    Equisolid,
+}
+
+/// The shape of the rays leaving the camera.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Rays spread from the camera center over the vertical field of view
+    /// `v_fov`, as in a pinhole or thin-lens camera.
+    #[default]
+    Perspective,
+    /// Parallel rays leaving a view plane of the given size in world units,
+    /// centered on `look_from`. Objects keep their size at any distance, as
+    /// in architectural and technical drawings. `v_fov` is ignored, and the
+    /// view plane is stretched if its aspect ratio differs from the image's.
+    Orthographic { width: Fl, height: Fl },
+    /// Longitude across and latitude down the image, covering every
+    /// direction, with the view direction in the middle. The image is always
+    /// twice as wide as it is tall.
+    Equirectangular,
+    /// A circular image, as wide as the shorter side of the image, covering
+    /// `fov` degrees around the view direction. Pixels outside the circle
+    /// are black.
+    Fisheye { model: FisheyeModel, fov: Fl },
+    /// Six square 90° perspective views on a 3 by 2 grid: +X, -X, +Y on the
+    /// top row and -Y, +Z, -Z on the bottom one, where X points to the right
+    /// of the camera, Y up and -Z along the view direction. The side faces
+    /// are upright, and the view direction is at the bottom of the +Y face
+    /// and at the top of the -Y one. The image is always 3:2.
+    CubeMap,
+}
+
+impl Projection {
+    /// Names accepted by [`Projection::from_name`].
+    pub const NAMES: [&'static str; 5] = [
+        "perspective",
+        "equirectangular",
+        "fisheye",
+        "fisheye-equisolid",
+        "cube-map",
+    ];
+
+    /// The projection of the given name. Fisheyes cover 180°.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let fisheye = |model| Self::Fisheye { model, fov: 180. };
+
+        match name {
+            "perspective" => Some(Self::Perspective),
+            "equirectangular" => Some(Self::Equirectangular),
+            "fisheye" => Some(fisheye(FisheyeModel::Equidistant)),
+            "fisheye-equisolid" => Some(fisheye(FisheyeModel::Equisolid)),
+            "cube-map" => Some(Self::CubeMap),
+            _ => None,
+        }
+    }
+
+    /// Whether rays leave from a single point in every direction. Depth of
+    /// field does not apply to these projections.
+    pub const fn is_panoramic(&self) -> bool {
+        matches!(
+            self,
+            Projection::Equirectangular | Projection::Fisheye { .. } | Projection::CubeMap
+        )
+    }
+
+    /// The aspect ratio the projection imposes on the image, if any.
+    pub const fn aspect_ratio(&self) -> Option<Fl> {
+        match self {
+            Projection::Equirectangular => Some(2.),
+            Projection::CubeMap => Some(1.5),
+            _ => None,
+        }
+    }
+
+    /// Direction of the ray through `(s, t)` of a panoramic projection, in
+    /// camera space, where `s` goes from 0 to 1 across the image and `t`
+    /// from 0 to 1 down. `None` if no ray goes through that point.
+    pub(crate) fn direction(&self, s: Fl, t: Fl, aspect_ratio: Fl) -> Option<Vec3> {
+        match *self {
+            Projection::Perspective | Projection::Orthographic { .. } => None,
+            Projection::Equirectangular => {
+                let longitude = (s - 0.5) * 2. * PI;
+                let latitude = (0.5 - t) * PI;
+
+                Some(Vec3::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    -latitude.cos() * longitude.cos(),
+                ))
+            }
+            Projection::Fisheye { model, fov } => {
+                // Coordinates where the image circle has a radius of one.
+                let (a, b) = if aspect_ratio >= 1. {
+                    ((2. * s - 1.) * aspect_ratio, 1. - 2. * t)
+                } else {
+                    (2. * s - 1., (1. - 2. * t) / aspect_ratio)
+                };
+
+                let r = a.hypot(b);
+
+                if r > 1. {
+                    return None;
+                }
+
+                let max_angle = fov.to_radians() / 2.;
+                let angle = match model {
+                    FisheyeModel::Equidistant => r * max_angle,
+                    FisheyeModel::Equisolid => 2. * (r * (max_angle / 2.).sin()).asin(),
+                };
+
+                let (a, b) = if r > 0. { (a / r, b / r) } else { (0., 0.) };
+                Some(Vec3::new(angle.sin() * a, angle.sin() * b, -angle.cos()))
+            }
+            Projection::CubeMap => {
+                let column = ((s * 3.) as usize).min(2);
+                let row = ((t * 2.) as usize).min(1);
+
+                // Coordinates on the face, from -1 to 1, right and up.
+                let a = 2. * (s * 3. - column as Fl) - 1.;
+                let b = 1. - 2. * (t * 2. - row as Fl);
+
+                Some(match row * 3 + column {
+                    0 => Vec3::new(1., b, a),
+                    1 => Vec3::new(-1., b, -a),
+                    2 => Vec3::new(a, 1., b),
+                    3 => Vec3::new(a, -1., -b),
+                    4 => Vec3::new(-a, b, 1.),
+                    _ => Vec3::new(a, b, -1.),
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! Reduction of images to a palette of at most 256 colors, for formats such
+//! as GIF which store indices into a palette rather than colors.
+
+use std::collections::HashMap;
+
+use crate::{
+    float::Fl,
+    ppm::{PPMColor, PPMImage},
+};
+
+/// Most colors a palette can hold, so that indices fit in a byte.
+pub const MAX_COLORS: usize = 256;
+
+/// How the palette is chosen from the colors of the images.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Quantizer {
+    /// Splits the box around the colors at the median of its longest side,
+    /// until there are enough boxes. Good with large areas of similar color.
+    #[default]
+    MedianCut,
+    /// Merges the leaves of an octree of the colors, starting with the least
+    /// used ones. Keeps rare but distinct colors.
+    Octree,
+}
+
+impl Quantizer {
+    /// Names accepted by [`Quantizer::from_name`].
+    pub const NAMES: [&'static str; 2] = ["median-cut", "octree"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "median-cut" => Some(Quantizer::MedianCut),
+            "octree" => Some(Quantizer::Octree),
+            _ => None,
+        }
+    }
+}
+
+/// How colors outside the palette are approximated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /// Each pixel takes the closest color of the palette, which bands smooth
+    /// gradients.
+    #[default]
+    None,
+    /// The error of each pixel is spread to its neighbours below and to the
+    /// right, which trades banding for noise.
+    FloydSteinberg,
+}
+
+impl Dither {
+    /// Names accepted by [`Dither::from_name`].
+    pub const NAMES: [&'static str; 2] = ["none", "floyd-steinberg"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Dither::None),
+            "floyd-steinberg" => Some(Dither::FloydSteinberg),
+            _ => None,
+        }
+    }
+}
+
+/// Colors which images are reduced to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<PPMColor>,
+}
+
+impl Palette {
+    /// A palette of at most `max_colors` colors, clamped to 1 to
+    /// [`MAX_COLORS`], shared by all the images.
+    pub fn new(images: &[PPMImage], max_colors: usize, quantizer: Quantizer) -> Self {
+        let max_colors = max_colors.clamp(1, MAX_COLORS);
+
+        let mut histogram: HashMap<PPMColor, u64> = HashMap::new();
+
+        for pixel in images.iter().flat_map(PPMImage::pixels) {
+            *histogram.entry(*pixel).or_default() += 1;
+        }
+
+        let mut colors: Vec<(PPMColor, u64)> = histogram.into_iter().collect();
+        // The order of a hash map changes between runs.
+        colors.sort_unstable_by_key(|&(color, _)| (color.r(), color.g(), color.b()));
+
+        let colors = if colors.len() <= max_colors {
+            colors.into_iter().map(|(color, _)| color).collect()
+        } else {
+            match quantizer {
+                Quantizer::MedianCut => median_cut(colors, max_colors),
+                Quantizer::Octree => octree(&colors, max_colors),
+            }
+        };
+
+        Self { colors }
+    }
+
+    pub fn colors(&self) -> &[PPMColor] {
+        &self.colors
+    }
+
+    /// Index of the color of the palette closest to `(r, g, b)`.
+    fn closest(&self, r: Fl, g: Fl, b: Fl) -> u8 {
+        let distance = |color: &PPMColor| {
+            let dr = Fl::from(color.r()) - r;
+            let dg = Fl::from(color.g()) - g;
+            let db = Fl::from(color.b()) - b;
+
+            dr * dr + dg * dg + db * db
+        };
+
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+            .map_or(0, |(i, _)| i as u8)
+    }
+
+    /// The index into the palette of every pixel of `image`, row by row.
+    pub fn indices(&self, image: &PPMImage, dither: Dither) -> Vec<u8> {
+        match dither {
+            Dither::None => {
+                let mut cache: HashMap<PPMColor, u8> = HashMap::new();
+
+                image
+                    .pixels()
+                    .iter()
+                    .map(|&pixel| {
+                        *cache.entry(pixel).or_insert_with(|| {
+                            self.closest(
+                                Fl::from(pixel.r()),
+                                Fl::from(pixel.g()),
+                                Fl::from(pixel.b()),
+                            )
+                        })
+                    })
+                    .collect()
+            }
+            Dither::FloydSteinberg => self.floyd_steinberg(image),
+        }
+    }
+
+    fn floyd_steinberg(&self, image: &PPMImage) -> Vec<u8> {
+        let (width, height) = (image.width(), image.height());
+        let mut indices = Vec::with_capacity(width * height);
+
+        // Errors carried to the current and the next row, with a pixel of
+        // padding on both sides.
+        let mut current = vec![[0.; 3]; width + 2];
+        let mut next = vec![[0.; 3]; width + 2];
+
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = image[(x, y)];
+                let error = current[x + 1];
+                let wanted = [
+                    (Fl::from(pixel.r()) + error[0]).clamp(0., 255.),
+                    (Fl::from(pixel.g()) + error[1]).clamp(0., 255.),
+                    (Fl::from(pixel.b()) + error[2]).clamp(0., 255.),
+                ];
+
+                let index = self.closest(wanted[0], wanted[1], wanted[2]);
+                let chosen = self.colors[usize::from(index)];
+                let chosen = [chosen.r(), chosen.g(), chosen.b()].map(Fl::from);
+
+                for channel in 0..3 {
+                    let error = wanted[channel] - chosen[channel];
+
+                    current[x + 2][channel] += error * 7. / 16.;
+                    next[x][channel] += error * 3. / 16.;
+                    next[x + 1][channel] += error * 5. / 16.;
+                    next[x + 2][channel] += error * 1. / 16.;
+                }
+
+                indices.push(index);
+            }
+
+            std::mem::swap(&mut current, &mut next);
+            next.fill([0.; 3]);
+        }
+
+        indices
+    }
+}
+
+/// The mean of colors, weighted by how often they appear.
+fn mean(colors: &[(PPMColor, u64)]) -> PPMColor {
+    let mut sum = [0; 3];
+    let mut count = 0;
+
+    for &(color, n) in colors {
+        sum[0] += u64::from(color.r()) * n;
+        sum[1] += u64::from(color.g()) * n;
+        sum[2] += u64::from(color.b()) * n;
+        count += n;
+    }
+
+    let [r, g, b] = sum.map(|sum| ((sum + count / 2) / count.max(1)) as u8);
+    PPMColor::new(r, g, b)
+}
+
+fn channel(color: PPMColor, channel: usize) -> u8 {
+    [color.r(), color.g(), color.b()][channel]
+}
+
+fn median_cut(colors: Vec<(PPMColor, u64)>, max_colors: usize) -> Vec<PPMColor> {
+    // The widest channel of a box and how wide it is.
+    let widest = |colors: &[(PPMColor, u64)]| {
+        (0..3)
+            .map(|c| {
+                let values = colors.iter().map(|&(color, _)| channel(color, c));
+                let range = values.clone().max().unwrap_or(0) - values.min().unwrap_or(0);
+
+                (c, range)
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap_or((0, 0))
+    };
+
+    let mut boxes = vec![colors];
+
+    while boxes.len() < max_colors {
+        // Split the box with the widest side, which leaves the largest
+        // errors.
+        let Some((i, c)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(i, colors)| (i, widest(colors)))
+            .max_by_key(|&(_, (_, range))| range)
+            .map(|(i, (c, _))| (i, c))
+        else {
+            break;
+        };
+
+        let mut colors = boxes.swap_remove(i);
+        colors.sort_unstable_by_key(|&(color, _)| channel(color, c));
+
+        // The median pixel, rather than the median color, so that common
+        // colors get more of the palette.
+        let total: u64 = colors.iter().map(|&(_, n)| n).sum();
+        let mut seen = 0;
+        let median = colors
+            .iter()
+            .position(|&(_, n)| {
+                seen += n;
+                2 * seen >= total
+            })
+            .unwrap_or(0);
+        let split = (median + 1).clamp(1, colors.len() - 1);
+
+        let upper = colors.split_off(split);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    boxes.iter().map(|colors| mean(colors)).collect()
+}
+
+/// A node of a color octree, whose children split each channel in two at
+/// the bit of their level.
+#[derive(Debug, Default, Clone)]
+struct OctreeNode {
+    children: [Option<usize>; 8],
+    sum: [u64; 3],
+    count: u64,
+    is_leaf: bool,
+}
+
+fn octree(colors: &[(PPMColor, u64)], max_colors: usize) -> Vec<PPMColor> {
+    const DEPTH: usize = 8;
+
+    let mut nodes = vec![OctreeNode::default()];
+    // Inner nodes of each level, which may be merged into leaves.
+    let mut levels: Vec<Vec<usize>> = vec![Vec::new(); DEPTH];
+
+    for &(color, n) in colors {
+        let mut node = 0;
+
+        for (level, inner_nodes) in levels.iter_mut().enumerate() {
+            let bit = 7 - level;
+            let child = (usize::from(color.r() >> bit & 1) << 2)
+                | (usize::from(color.g() >> bit & 1) << 1)
+                | usize::from(color.b() >> bit & 1);
+
+            node = match nodes[node].children[child] {
+                Some(child) => child,
+                None => {
+                    if nodes[node].children.iter().all(Option::is_none) {
+                        inner_nodes.push(node);
+                    }
+
+                    nodes.push(OctreeNode::default());
+                    let id = nodes.len() - 1;
+                    nodes[node].children[child] = Some(id);
+                    id
+                }
+            };
+        }
+
+        let leaf = &mut nodes[node];
+        leaf.is_leaf = true;
+        leaf.count += n;
+        leaf.sum[0] += u64::from(color.r()) * n;
+        leaf.sum[1] += u64::from(color.g()) * n;
+        leaf.sum[2] += u64::from(color.b()) * n;
+    }
+
+    let mut leaves = colors.len();
+
+    // Merge the children of the deepest inner nodes into them, least used
+    // first, until few enough leaves are left.
+    for level in (0..DEPTH).rev() {
+        let mut candidates = std::mem::take(&mut levels[level]);
+        let weight = |node: &OctreeNode, nodes: &[OctreeNode]| -> u64 {
+            node.children
+                .iter()
+                .flatten()
+                .map(|&child| nodes[child].count)
+                .sum()
+        };
+        candidates.sort_unstable_by_key(|&node| std::cmp::Reverse(weight(&nodes[node], &nodes)));
+
+        while leaves > max_colors {
+            let Some(node) = candidates.pop() else {
+                break;
+            };
+
+            let children: Vec<usize> = nodes[node].children.iter().flatten().copied().collect();
+
+            for &child in &children {
+                let (sum, count) = (nodes[child].sum, nodes[child].count);
+                let parent = &mut nodes[node];
+
+                parent.count += count;
+                for (total, value) in parent.sum.iter_mut().zip(sum) {
+                    *total += value;
+                }
+            }
+
+            let parent = &mut nodes[node];
+            parent.children = [None; 8];
+            parent.is_leaf = true;
+            leaves -= children.len() - 1;
+        }
+
+        if leaves <= max_colors {
+            break;
+        }
+    }
+
+    let mut palette = Vec::with_capacity(leaves);
+    let mut stack = vec![0];
+
+    while let Some(node) = stack.pop() {
+        let node = &nodes[node];
+
+        if node.is_leaf {
+            let [r, g, b] = node
+                .sum
+                .map(|sum| ((sum + node.count / 2) / node.count.max(1)) as u8);
+            palette.push(PPMColor::new(r, g, b));
+        } else {
+            stack.extend(node.children.iter().flatten());
+        }
+    }
+
+    palette
+}
@@ -1,11 +1,20 @@
-use crate::{float::Fl, vec3::{Pos3, Vec3}};
+//! Rays traced through the scene.
 
+use crate::{
+    float::Fl,
+    medium::{Medium, MediumStack},
+    vec3::{Pos3, Vec3},
+};
+
+/// A half-line at a given time, optionally tagged with the pixel it was
+/// traced for, through the media of the objects its origin is inside.
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct Ray {
     origin: Pos3,
     direction: Vec3,
     time: Fl,
     pixel: Option<(usize, usize)>,
+    media: MediumStack,
 }
 
 impl Ray {
@@ -15,6 +24,7 @@ impl Ray {
             direction,
             time: 0.,
             pixel: None,
+            media: MediumStack::EMPTY,
         }
     }
 
@@ -27,12 +37,7 @@ impl Ray {
     }
 
     pub const fn at_time(&self, time: Fl) -> Self {
-        Self {
-            direction: self.direction,
-            origin: self.origin,
-            time,
-            pixel: self.pixel,
-        }
+        Self { time, ..*self }
     }
 
     pub const fn at_time_of(&self, other: &Self) -> Self {
@@ -49,10 +54,8 @@ impl Ray {
 
     pub const fn for_pixel(&self, pixel: (usize, usize)) -> Self {
         Self {
-            direction: self.direction,
-            origin: self.origin,
-            time: self.time,
             pixel: Some(pixel),
+            ..*self
         }
     }
 
@@ -72,6 +75,26 @@ impl Ray {
         self.pixel
     }
 
+    pub const fn in_media(&self, media: MediumStack) -> Self {
+        Self { media, ..*self }
+    }
+
+    /// The same media as `other`, for rays which scatter without crossing
+    /// a surface.
+    pub const fn in_media_of(&self, other: &Self) -> Self {
+        self.in_media(other.media)
+    }
+
+    pub const fn media(&self) -> &MediumStack {
+        &self.media
+    }
+
+    /// The medium the ray travels through.
+    pub fn medium(&self) -> Medium {
+        self.media.current()
+    }
+
+    /// Returns the point at distance `t` (in units of direction) on the ray.
     pub fn at(&self, t: Fl) -> Pos3 {
         self.origin + self.direction * t
     }
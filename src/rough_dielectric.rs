@@ -0,0 +1,203 @@
+//! Rough transparent surfaces, such as frosted glass and etched surfaces.
+
+use crate::{
+    float::{random, Fl},
+    hittable::HitRecord,
+    material::{Material, ScatterRecord},
+    medium::Medium,
+    microfacet::{Ggx, TangentFrame},
+    ray::Ray,
+    vec3::{Color, Vec3},
+};
+
+/// A dielectric whose surface is made of GGX microfacets, each of which
+/// reflects or refracts, following Walter et al., "Microfacet Models for
+/// Refraction through Rough Surfaces".
+///
+/// Directions of [`RoughDielectricMaterial::eval`] and
+/// [`RoughDielectricMaterial::sample`] are in a local frame where `+z` is
+/// the outward normal, both pointing away from the surface. As for
+/// [`DielectricMaterial`](crate::dielectric::DielectricMaterial), radiance
+/// is not scaled by the squared ratio of indices when refracting, which
+/// cancels out for light which leaves the object again. The scattering is
+/// then reciprocal once divided by the squared index on the side of the
+/// light.
+///
+/// Both functions take the index of the medium outside to be 1. When
+/// scattering rays, light bends by the ratio of the indices of the media on
+/// both sides, such as rough glass in water.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct RoughDielectricMaterial {
+    refraction_index: Fl,
+    roughness: Fl,
+}
+
+impl RoughDielectricMaterial {
+    /// A dielectric of the given refractive index, which is absolute: light
+    /// bends by its ratio to the index of the medium around the object. A
+    /// roughness of 0 is smooth, and 1 is very rough.
+    pub fn new(refraction_index: Fl, roughness: Fl) -> Self {
+        Self {
+            refraction_index,
+            roughness,
+        }
+    }
+
+    pub const fn refraction_index(&self) -> Fl {
+        self.refraction_index
+    }
+
+    pub const fn roughness(&self) -> Fl {
+        self.roughness
+    }
+
+    fn ggx(&self) -> Ggx {
+        Ggx::new(self.roughness, self.roughness)
+    }
+
+    /// Indices of refraction on the side of `w` and on the other side.
+    fn indices(&self, w: Vec3) -> (Fl, Fl) {
+        if w.z() > 0. {
+            (1., self.refraction_index)
+        } else {
+            (self.refraction_index, 1.)
+        }
+    }
+
+    /// The scattering function for light arriving from `wi` and leaving
+    /// towards `wo`, without the cosine of `wi`.
+    pub fn eval(&self, wo: Vec3, wi: Vec3) -> Fl {
+        if wo.z() == 0. || wi.z() == 0. {
+            return 0.;
+        }
+
+        let ggx = self.ggx();
+        let (eta_o, eta_t) = self.indices(wo);
+        let cosines = (wo.z() * wi.z()).abs();
+
+        if wo.z() * wi.z() > 0. {
+            let h = (wo + wi).normalize();
+            let h = if h.z() < 0. { -h } else { h };
+
+            let fresnel = fresnel_dielectric(wo.dot(&h).abs(), eta_o / eta_t);
+
+            return fresnel * ggx.d(h) * ggx.g2(wo, wi) / (4. * cosines);
+        }
+
+        // The microfacet normal which refracts `wo` into `wi`.
+        let h = -(wo * eta_o + wi * eta_t);
+
+        if h.near_zero() {
+            return 0.;
+        }
+
+        let h = h.normalize();
+        let h = if h.z() < 0. { -h } else { h };
+        let (wo_h, wi_h) = (wo.dot(&h), wi.dot(&h));
+
+        // Each direction must be on the same side of the microfacet as of
+        // the surface.
+        if wo_h * wo.z() <= 0. || wi_h * wi.z() <= 0. {
+            return 0.;
+        }
+
+        let fresnel = fresnel_dielectric(wo_h.abs(), eta_o / eta_t);
+        let denominator = eta_o * wo_h + eta_t * wi_h;
+
+        (1. - fresnel) * ggx.d(h) * ggx.g2(wo, wi) * (wo_h * wi_h).abs() * eta_t * eta_t
+            / (cosines * denominator * denominator)
+    }
+
+    /// Samples the direction `wi` light arrives from to leave towards `wo`,
+    /// from the uniform numbers `u`, and returns it with its weight: the
+    /// scattering function times the cosine of `wi` over the density. `None`
+    /// if the sampled direction is shadowed.
+    pub fn sample(&self, wo: Vec3, u: (Fl, Fl, Fl)) -> Option<(Vec3, Fl)> {
+        if wo.z() == 0. {
+            return None;
+        }
+
+        // Sample from above the surface, flipping back at the end.
+        let flip = |w: Vec3| Vec3::new(w.x(), w.y(), -w.z());
+        let (eta_o, eta_t) = self.indices(wo);
+        let inside = wo.z() < 0.;
+        let wo = if inside { flip(wo) } else { wo };
+
+        let ggx = self.ggx();
+        let m = ggx.sample_visible_normal(wo, u.0, u.1);
+        let eta = eta_o / eta_t;
+        let fresnel = fresnel_dielectric(wo.dot(&m), eta);
+
+        // Reflection and refraction are chosen in proportion to their
+        // Fresnel weights, which then cancel out. Totally reflected light
+        // always reflects.
+        let wi = if u.2 < fresnel {
+            let wi = (-wo).reflect(&m);
+            (wi.z() > 0.).then_some(wi)?
+        } else {
+            let wi = (-wo).refract(&m, eta).normalize();
+            (wi.z() < 0.).then_some(wi)?
+        };
+
+        let weight = ggx.g2(wo, wi) / ggx.g1(wo);
+        let wi = if inside { flip(wi) } else { wi };
+
+        Some((wi, weight))
+    }
+}
+
+/// Fresnel reflectance of unpolarized light arriving at an angle of cosine
+/// `cos_i` on an interface with a relative index of refraction `eta`, the
+/// index on the side of the light over the other one. One on total internal
+/// reflection.
+fn fresnel_dielectric(cos_i: Fl, eta: Fl) -> Fl {
+    let cos_i = cos_i.clamp(0., 1.);
+    let sin2_t = eta * eta * (1. - cos_i * cos_i);
+
+    if sin2_t >= 1. {
+        return 1.;
+    }
+
+    let cos_t = (1. - sin2_t).sqrt();
+    let rs = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let rp = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+
+    (rs * rs + rp * rp) / 2.
+}
+
+impl Material for RoughDielectricMaterial {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let outward_normal = if rec.front_face {
+            rec.normal
+        } else {
+            -rec.normal
+        };
+        let frame = TangentFrame::new(outward_normal);
+        let wo = frame.to_local(-r_in.direction().normalize());
+
+        // Only the ratio of the indices on both sides bends light.
+        let around = r_in.media().around(rec.object_id);
+        let surface = Self::new(
+            self.refraction_index / around.refraction_index(),
+            self.roughness,
+        );
+
+        let (wi, weight) = surface.sample(wo, (random(), random(), random()))?;
+
+        // Transmitted rays enter or leave the object, which is clear.
+        let media = if wi.z() * wo.z() > 0. {
+            *r_in.media()
+        } else {
+            let medium = Medium::new(self.refraction_index, Color::new(0., 0., 0.), 0);
+            r_in.media().crossed(rec.object_id, medium, wo.z() > 0.)
+        };
+
+        Some(ScatterRecord {
+            attenuation: Color::new(weight, weight, weight),
+            scattered: Ray::new(rec.p, frame.to_world(wi))
+                .at_time_of(r_in)
+                .in_media(media)
+                .for_pixel_of(r_in),
+        })
+    }
+}
@@ -0,0 +1,169 @@
+//! Built-in scenes.
+
+use crate::{
+    camera::CameraOptions,
+    dielectric::DielectricMaterial,
+    float::{random, with_random_source, Fl, RandomSource},
+    hittable_list::HittableList,
+    interval::Interval,
+    lambertian::LambertianMaterial,
+    material::MaterialObject,
+    metal::MetalMaterial,
+    sphere::Sphere,
+    vec3::{Color, Pos3, Vec3},
+};
+
+const BOOK_COVER_SEED: u64 = 42;
+
+/// A world together with the camera looking at it.
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub world: HittableList,
+    pub camera: CameraOptions,
+}
+
+impl Scene {
+    /// Names accepted by [`Scene::from_name`].
+    pub const NAMES: [&'static str; 2] = ["book-cover", "three-spheres"];
+
+    /// Returns the built-in scene with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "book-cover" => Some(Self::book_cover()),
+            "three-spheres" => Some(Self::three_spheres()),
+            _ => None,
+        }
+    }
+
+    /// The final scene of the book: a field of random small spheres around
+    /// three large ones. The small spheres are placed using a fixed seed, so
+    /// that a render can be resumed or compared with another.
+    pub fn book_cover() -> Self {
+        with_random_source(
+            RandomSource::seeded(BOOK_COVER_SEED),
+            Self::random_book_cover,
+        )
+        .0
+    }
+
+    fn random_book_cover() -> Self {
+        let mut world = HittableList::default();
+
+        let ground_material: MaterialObject =
+            LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into();
+        world.add(Sphere::new(Pos3::new(0., -1000., 0.), 1000., ground_material).into());
+
+        for a in -11..11 {
+            for b in -11..11 {
+                let choose_mat = random();
+                let center = Pos3::new((a as Fl) + 0.9 * random(), 0.2, (b as Fl) + 0.9 * random());
+
+                if choose_mat < 0.8 {
+                    world.add(
+                        Sphere::new_moving(
+                            center,
+                            center + Vec3::new(0., random() * 0.5, 0.),
+                            0.2,
+                            LambertianMaterial::new(Color::random()).into(),
+                        )
+                        .into(),
+                    );
+                } else if choose_mat < 0.95 {
+                    world.add(
+                        Sphere::new(
+                            center,
+                            0.2,
+                            MetalMaterial::new(
+                                Color::random_between(Interval::new(0.5, 1.)),
+                                Interval::new(0., 0.5).random(),
+                            )
+                            .into(),
+                        )
+                        .into(),
+                    );
+                } else {
+                    world.add(Sphere::new(center, 0.2, DielectricMaterial::new(1.5).into()).into());
+                }
+            }
+        }
+
+        world.add(
+            Sphere::new(
+                Pos3::new(0., 1., 0.),
+                1.,
+                DielectricMaterial::new(1.5).into(),
+            )
+            .into(),
+        );
+
+        world.add(
+            Sphere::new(
+                Pos3::new(-4., 1., 0.),
+                1.,
+                LambertianMaterial::new(Color::new(0.4, 0.2, 0.1)).into(),
+            )
+            .into(),
+        );
+
+        world.add(
+            Sphere::new(
+                Pos3::new(4., 1., 0.),
+                1.,
+                MetalMaterial::new(Color::new(0.7, 0.6, 0.5), 0.).into(),
+            )
+            .into(),
+        );
+
+        let camera = CameraOptions {
+            aspect_ratio: 16. / 9.,
+            image_width: 400,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            v_fov: 20.,
+            look_from: Pos3::new(13., 2., 3.),
+            look_at: Pos3::new(0., 0., -0.),
+            vup: Vec3::new(0., 1., 0.),
+            defocus_angle: 0.6,
+            focus_dist: 10.,
+            ..CameraOptions::default()
+        };
+
+        Self { world, camera }
+    }
+
+    /// The scene rendered by `fish/path-trace.fish`: a matte sphere between a
+    /// hollow glass sphere and a fuzzy metal one.
+    pub fn three_spheres() -> Self {
+        let material_ground: MaterialObject =
+            LambertianMaterial::new(Color::new(0.8, 0.8, 0.)).into();
+        let material_center: MaterialObject =
+            LambertianMaterial::new(Color::new(0.1, 0.2, 0.5)).into();
+        let material_left: MaterialObject = DielectricMaterial::new(1.5).into();
+        let material_bubble: MaterialObject = DielectricMaterial::new(1. / 1.5).into();
+        let material_right: MaterialObject =
+            MetalMaterial::new(Color::new(0.8, 0.6, 0.2), 1.).into();
+
+        let mut world = HittableList::default();
+        world.add(Sphere::new(Pos3::new(0., -100.5, -1.), 100., material_ground).into());
+        world.add(Sphere::new(Pos3::new(0., 0., -1.2), 0.5, material_center).into());
+        world.add(Sphere::new(Pos3::new(-1., 0., -1.), 0.5, material_left).into());
+        world.add(Sphere::new(Pos3::new(-1., 0., -1.), 0.4, material_bubble).into());
+        world.add(Sphere::new(Pos3::new(1., 0., -1.), 0.5, material_right).into());
+
+        let camera = CameraOptions {
+            aspect_ratio: 16. / 9.,
+            image_width: 400,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            v_fov: 20.,
+            look_from: Pos3::new(-2., 2., 1.),
+            look_at: Pos3::new(0., 0., -1.),
+            vup: Vec3::new(0., 1., 0.),
+            defocus_angle: 10.,
+            focus_dist: 3.4,
+            ..CameraOptions::default()
+        };
+
+        Self { world, camera }
+    }
+}
@@ -0,0 +1,101 @@
+//! When the camera lets light in, which decides the motion blur of moving
+//! objects.
+
+use std::sync::Arc;
+
+use crate::float::Fl;
+
+/// How open the shutter is over the exposure.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum ShutterCurve {
+    /// Fully open from the opening time to the closing time.
+    #[default]
+    Box,
+    /// Opening linearly until the middle of the exposure, then closing.
+    Triangle,
+    /// Openness at evenly spaced times, held constant between them. Only
+    /// the ratios between the values matter.
+    Custom(Arc<[Fl]>),
+}
+
+impl ShutterCurve {
+    /// Names accepted by [`ShutterCurve::from_name`].
+    pub const NAMES: [&'static str; 2] = ["box", "triangle"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "box" => Some(Self::Box),
+            "triangle" => Some(Self::Triangle),
+            _ => None,
+        }
+    }
+
+    /// Maps `u`, uniform in `[0, 1)`, to a fraction of the exposure
+    /// distributed as the curve.
+    fn sample(&self, u: Fl) -> Fl {
+        match self {
+            ShutterCurve::Box => u,
+            ShutterCurve::Triangle => {
+                if u < 0.5 {
+                    (u / 2.).sqrt()
+                } else {
+                    1. - ((1. - u) / 2.).sqrt()
+                }
+            }
+            ShutterCurve::Custom(weights) => {
+                let total: Fl = weights.iter().sum();
+
+                if total <= 0. {
+                    return u;
+                }
+
+                let mut target = u * total;
+
+                for (i, &weight) in weights.iter().enumerate() {
+                    if target < weight {
+                        return (i as Fl + target / weight) / weights.len() as Fl;
+                    }
+
+                    target -= weight;
+                }
+
+                1.
+            }
+        }
+    }
+}
+
+/// When each ray of a render is traced, in scene time. Moving objects are
+/// positioned in the same time, so changing the shutter changes how much of
+/// their motion is captured, not the motion itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shutter {
+    pub open: Fl,
+    pub close: Fl,
+    pub curve: ShutterCurve,
+    /// Time the shutter takes to sweep from the top row of the image to the
+    /// bottom one. Each row is exposed from `open` to `close`, delayed by
+    /// its share of the readout. Zero for a global shutter, which exposes
+    /// every row at once.
+    pub rolling_readout: Fl,
+}
+
+impl Default for Shutter {
+    fn default() -> Self {
+        Self {
+            open: 0.,
+            close: 1.,
+            curve: ShutterCurve::default(),
+            rolling_readout: 0.,
+        }
+    }
+}
+
+impl Shutter {
+    /// The time of a ray through `row`, the position of the ray down the
+    /// image from 0 to 1, where `u` is uniform in `[0, 1)`.
+    pub(crate) fn sample_time(&self, u: Fl, row: Fl) -> Fl {
+        let delay = self.rolling_readout * row;
+        self.open + delay + (self.close - self.open) * self.curve.sample(u)
+    }
+}
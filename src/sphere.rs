@@ -1,10 +1,16 @@
+//! Spheres, optionally moving during the exposure.
+
 use crate::{
-    float::Fl, hittable::{HitRecord, Hittable}, interval::Interval, material::MaterialObject, ray::Ray, vec3::Pos3
+    float::Fl, hittable::{HitRecord, Hittable}, interval::Interval, material::MaterialObject, ray::Ray, stats, vec3::{Pos3, Vec3}
 };
 
+/// A sphere with a material.
 #[derive(Debug, Clone)]
 pub struct Sphere {
-    center: Ray,
+    /// Center at time 0.
+    center: Pos3,
+    /// Distance the center moves per unit of time.
+    velocity: Vec3,
     radius: Fl,
     mat: MaterialObject,
 }
@@ -12,24 +18,62 @@ pub struct Sphere {
 impl Sphere {
     pub fn new(center: Pos3, radius: Fl, mat: MaterialObject) -> Self {
         Self {
-            center: Ray::new(center, Pos3::default()),
+            center,
+            velocity: Vec3::default(),
             radius: radius.max(0.),
             mat,
         }
     }
 
+    /// A sphere moving from `center1` at time 0 to `center2` at time 1.
+    /// Times are scene times: the sphere keeps moving at the same speed
+    /// before and after, whatever the shutter of the camera.
     pub fn new_moving(center1: Pos3, center2: Pos3, radius: Fl, mat: MaterialObject) -> Self {
+        Self::new_moving_between((center1, 0.), (center2, 1.), radius, mat)
+    }
+
+    /// A sphere moving at constant speed through each center at its time.
+    ///
+    /// Panics if both times are equal, as the speed would be infinite.
+    pub fn new_moving_between(
+        (center1, time1): (Pos3, Fl),
+        (center2, time2): (Pos3, Fl),
+        radius: Fl,
+        mat: MaterialObject,
+    ) -> Self {
+        assert!(
+            time1 != time2,
+            "the centers of a moving sphere need different times"
+        );
+        let velocity = (center2 - center1) / (time2 - time1);
+
         Self {
-            center: Ray::new(center1, center2 - center1),
+            center: center1 - velocity * time1,
+            velocity,
             radius: radius.max(0.),
             mat,
         }
     }
-}
 
-impl Hittable for Sphere {
-    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
-        let current_center = self.center.at(r.time());
+    /// Returns the center of the sphere at the given time.
+    pub fn center(&self, time: Fl) -> Pos3 {
+        self.center + self.velocity * time
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.velocity != Vec3::default()
+    }
+
+    pub const fn radius(&self) -> Fl {
+        self.radius
+    }
+
+    pub const fn material(&self) -> &MaterialObject {
+        &self.mat
+    }
+
+    fn intersect(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let current_center = self.center(r.time());
         let oc = &current_center - r.origin();
         let a = r.direction().squared_abs();
         let h = r.direction().dot(&oc);
@@ -58,3 +102,11 @@ impl Hittable for Sphere {
         Some(HitRecord::new(t, p, self.mat.clone(), r, outward_normal))
     }
 }
+
+impl Hittable for Sphere {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let rec = self.intersect(r, ray_t);
+        stats::count_sphere_test(rec.is_some());
+        rec
+    }
+}
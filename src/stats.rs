@@ -0,0 +1,199 @@
+//! Counters of the work done while rendering.
+//!
+//! Every thread counts into its own thread-local [`RenderStats`], so counting
+//! needs no locking. A render takes the counters of each thread with
+//! [`take`] once the thread is done with a piece of the image, and adds them
+//! up.
+
+use std::{cell::RefCell, fmt::Display, ops::AddAssign, time::Duration};
+
+/// Why a path stopped bouncing.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Termination {
+    /// The path reached `max_depth` bounces.
+    MaxDepth,
+    /// The material absorbed the ray.
+    Absorbed,
+    /// The ray left the scene.
+    Escaped,
+}
+
+/// Counters of the work done by a render.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct RenderStats {
+    /// Samples taken, including those the lens or projection sent no ray
+    /// for.
+    pub samples: u64,
+    /// Rays traced from the camera, one per sample which has a ray.
+    pub camera_rays: u64,
+    /// Rays scattered by a material.
+    pub bounce_rays: u64,
+    /// Ray-sphere intersection tests.
+    pub sphere_tests: u64,
+    /// Ray-sphere intersection tests which found a hit.
+    pub sphere_hits: u64,
+    /// Count of paths by the number of rays in them.
+    pub path_lengths: Vec<u64>,
+    pub max_depth_terminated: u64,
+    pub absorbed: u64,
+    pub escaped: u64,
+    /// Wall-clock time of the render.
+    pub elapsed: Duration,
+}
+
+impl RenderStats {
+    const fn new() -> Self {
+        Self {
+            samples: 0,
+            camera_rays: 0,
+            bounce_rays: 0,
+            sphere_tests: 0,
+            sphere_hits: 0,
+            path_lengths: Vec::new(),
+            max_depth_terminated: 0,
+            absorbed: 0,
+            escaped: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn rays(&self) -> u64 {
+        self.camera_rays + self.bounce_rays
+    }
+
+    /// Millions of rays traced per second of wall-clock time.
+    pub fn mrays_per_second(&self) -> f64 {
+        self.rays() as f64 / self.elapsed.as_secs_f64() / 1e6
+    }
+
+    /// Wall-clock time of the render divided by the samples taken.
+    pub fn time_per_sample(&self) -> Duration {
+        self.elapsed
+            .checked_div(self.samples.try_into().unwrap_or(u32::MAX))
+            .unwrap_or_default()
+    }
+}
+
+impl AddAssign<&RenderStats> for RenderStats {
+    fn add_assign(&mut self, rhs: &RenderStats) {
+        self.samples += rhs.samples;
+        self.camera_rays += rhs.camera_rays;
+        self.bounce_rays += rhs.bounce_rays;
+        self.sphere_tests += rhs.sphere_tests;
+        self.sphere_hits += rhs.sphere_hits;
+        self.max_depth_terminated += rhs.max_depth_terminated;
+        self.absorbed += rhs.absorbed;
+        self.escaped += rhs.escaped;
+        self.elapsed += rhs.elapsed;
+
+        if self.path_lengths.len() < rhs.path_lengths.len() {
+            self.path_lengths.resize(rhs.path_lengths.len(), 0);
+        }
+
+        for (count, rhs) in self.path_lengths.iter_mut().zip(&rhs.path_lengths) {
+            *count += rhs;
+        }
+    }
+}
+
+impl Display for RenderStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let paths = self.max_depth_terminated + self.absorbed + self.escaped;
+        let percent = |count: u64| 100. * count as f64 / paths.max(1) as f64;
+
+        writeln!(
+            f,
+            "{} rays in {:.2?}: {:.3} Mrays/s, {:.2?} per sample",
+            self.rays(),
+            self.elapsed,
+            self.mrays_per_second(),
+            self.time_per_sample(),
+        )?;
+        writeln!(
+            f,
+            "  {} samples, {} camera rays, {} bounce rays",
+            self.samples, self.camera_rays, self.bounce_rays
+        )?;
+        writeln!(
+            f,
+            "  {} sphere tests, {} hits ({:.1}%)",
+            self.sphere_tests,
+            self.sphere_hits,
+            100. * self.sphere_hits as f64 / self.sphere_tests.max(1) as f64,
+        )?;
+        write!(
+            f,
+            "  paths: {:.1}% escaped, {:.1}% absorbed, {:.1}% hit max depth",
+            percent(self.escaped),
+            percent(self.absorbed),
+            percent(self.max_depth_terminated),
+        )?;
+
+        if paths == 0 {
+            return Ok(());
+        }
+
+        write!(f, "\n  path lengths:")?;
+
+        // Only the common lengths, the long tail is summed up.
+        let mut counted = 0;
+
+        for (length, &count) in self.path_lengths.iter().enumerate() {
+            if percent(paths - counted) < 1. {
+                return write!(f, " longer: {:.1}%", percent(paths - counted));
+            }
+
+            if count > 0 {
+                write!(f, " {length}: {:.1}%", percent(count))?;
+                counted += count;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+thread_local! {
+    static STATS: RefCell<RenderStats> = const { RefCell::new(RenderStats::new()) };
+}
+
+/// Returns the counters of the current thread, and resets them.
+pub fn take() -> RenderStats {
+    STATS.take()
+}
+
+pub(crate) fn count_sample() {
+    STATS.with_borrow_mut(|stats| stats.samples += 1);
+}
+
+pub(crate) fn count_camera_ray() {
+    STATS.with_borrow_mut(|stats| stats.camera_rays += 1);
+}
+
+pub(crate) fn count_bounce_ray() {
+    STATS.with_borrow_mut(|stats| stats.bounce_rays += 1);
+}
+
+pub(crate) fn count_sphere_test(hit: bool) {
+    STATS.with_borrow_mut(|stats| {
+        stats.sphere_tests += 1;
+        stats.sphere_hits += u64::from(hit);
+    });
+}
+
+/// Counts a path of `length` rays which ended because of `termination`.
+pub(crate) fn count_path(length: usize, termination: Termination) {
+    STATS.with_borrow_mut(|stats| {
+        if stats.path_lengths.len() <= length {
+            stats.path_lengths.resize(length + 1, 0);
+        }
+
+        stats.path_lengths[length] += 1;
+
+        match termination {
+            Termination::MaxDepth => stats.max_depth_terminated += 1,
+            Termination::Absorbed => stats.absorbed += 1,
+            Termination::Escaped => stats.escaped += 1,
+        }
+    });
+}
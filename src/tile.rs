@@ -0,0 +1,151 @@
+//! Splitting the image into square tiles, rendered in a chosen order.
+
+use crate::float::Fl;
+
+/// A rectangle of pixels. Tiles on the right and bottom edges of the image
+/// may be smaller than the others.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Tile {
+    /// Iterates over the pixels of the tile, row by row.
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize)> {
+        let Tile {
+            x,
+            y,
+            width,
+            height,
+        } = *self;
+
+        (y..y + height).flat_map(move |y| (x..x + width).map(move |x| (x, y)))
+    }
+
+    pub const fn area(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+/// The order in which tiles are handed out to threads.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TileOrder {
+    /// Left to right, top to bottom.
+    Scanline,
+    /// From the center of the image outwards, where the subject usually is.
+    #[default]
+    Spiral,
+    /// Along a Hilbert curve, so consecutive tiles are mostly neighbours. The
+    /// curve fills the smallest square grid of a power of two tiles around
+    /// the image, and jumps where it leaves the image and comes back.
+    Hilbert,
+}
+
+impl TileOrder {
+    /// Names accepted by [`TileOrder::from_name`].
+    pub const NAMES: [&'static str; 3] = ["scanline", "spiral", "hilbert"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "scanline" => Some(Self::Scanline),
+            "spiral" => Some(Self::Spiral),
+            "hilbert" => Some(Self::Hilbert),
+            _ => None,
+        }
+    }
+}
+
+/// How the image is split into tiles.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct TileOptions {
+    /// Width and height of a tile in pixels.
+    pub size: usize,
+    pub order: TileOrder,
+}
+
+impl Default for TileOptions {
+    fn default() -> Self {
+        Self {
+            size: 16,
+            order: TileOrder::default(),
+        }
+    }
+}
+
+impl TileOptions {
+    /// Splits an image of the given size into tiles, in the chosen order.
+    pub fn tiles(&self, width: usize, height: usize) -> Vec<Tile> {
+        let size = self.size.max(1);
+        let columns = width.div_ceil(size);
+        let rows = height.div_ceil(size);
+
+        let mut grid: Vec<(usize, usize)> = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .collect();
+
+        match self.order {
+            TileOrder::Scanline => {}
+            TileOrder::Spiral => {
+                let center_x = (columns as Fl - 1.) / 2.;
+                let center_y = (rows as Fl - 1.) / 2.;
+
+                grid.sort_by(|&a, &b| {
+                    let key = |(column, row): (usize, usize)| {
+                        let dx = column as Fl - center_x;
+                        let dy = row as Fl - center_y;
+                        (dx.abs().max(dy.abs()), dy.atan2(dx))
+                    };
+
+                    key(a).partial_cmp(&key(b)).unwrap()
+                });
+            }
+            TileOrder::Hilbert => {
+                let side = columns.max(rows).next_power_of_two();
+                grid.sort_by_key(|&(column, row)| hilbert_index(side, column, row));
+            }
+        }
+
+        grid.into_iter()
+            .map(|(column, row)| {
+                let x = column * size;
+                let y = row * size;
+
+                Tile {
+                    x,
+                    y,
+                    width: size.min(width - x),
+                    height: size.min(height - y),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Position of the cell on a Hilbert curve filling a `side` by `side` grid,
+/// where `side` is a power of two.
+fn hilbert_index(side: usize, mut x: usize, mut y: usize) -> usize {
+    let mut index = 0;
+    let mut s = side / 2;
+
+    while s > 0 {
+        let rx = usize::from(x & s != 0);
+        let ry = usize::from(y & s != 0);
+        index += s * s * ((3 * rx) ^ ry);
+
+        if ry == 0 {
+            if rx == 1 {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    index
+}
@@ -1,7 +1,10 @@
+//! Three-dimensional vectors, used for positions, directions and colors.
+
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use crate::{float::{random, Fl}, interval::Interval, ppm::PPMColor};
 
+/// A three-dimensional vector.
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct Vec3(Fl, Fl, Fl);
 
@@ -46,6 +49,7 @@ impl Vec3 {
         self / self.abs()
     }
 
+    /// Returns a vector with each component in `[0, 1)`.
     pub fn random() -> Self {
         Vec3(random(), random(), random())
     }
@@ -54,6 +58,7 @@ impl Vec3 {
         Vec3(interval.random(), interval.random(), interval.random())
     }
 
+    /// Returns a random vector inside the unit sphere.
     pub fn random_in_unit_sphere() -> Self {
         loop {
             let p = Self::random_between(Interval::new(-1., 1.));
@@ -64,6 +69,7 @@ impl Vec3 {
         }
     }
 
+    /// Returns a random vector inside the unit disk on the XY plane.
     pub fn random_in_unit_disk() -> Self {
         loop {
             let p = Vec3(
@@ -78,10 +84,12 @@ impl Vec3 {
         }
     }
 
+    /// Returns a random unit vector.
     pub fn random_normalized() -> Self {
         Self::random_in_unit_sphere().normalize()
     }
 
+    /// Returns a random unit vector on the same side as `normal`.
     pub fn random_on_hemisphere(normal: &Self) -> Self {
         let vec = Self::random_normalized();
 
@@ -92,15 +100,23 @@ impl Vec3 {
         }
     }
 
+    /// Relative luminance of a linear color, using the Rec. 709 primaries.
+    pub fn luminance(&self) -> Fl {
+        0.2126 * self.0 + 0.7152 * self.1 + 0.0722 * self.2
+    }
+
+    /// Whether every component is close to zero.
     pub fn near_zero(&self) -> bool {
         let s = 1e-8;
         (self.x().abs() < s) && (self.y().abs() < s) && (self.z().abs() < s)
     }
 
+    /// Reflects the vector off a surface with the given unit normal.
     pub fn reflect(&self, normal: &Self) -> Self {
         *self - normal * (2. * self.dot(normal))
     }
 
+    /// Refracts the unit vector through a surface with the given unit normal.
     pub fn refract(&self, normal: &Self, eta_i_over_eta_t: Fl) -> Self {
         let cos_theta = (-self).dot(normal).min(1.);
         let r_out_perpendicular = (*self + normal * cos_theta) * eta_i_over_eta_t;
@@ -125,6 +141,7 @@ impl Neg for &Vec3 {
     }
 }
 
+/// Gamma-corrects the linear color and quantizes it to 8 bits.
 impl From<Vec3> for PPMColor {
     fn from(val: Vec3) -> Self {
         let intensity = Interval::new(0., 0.999);
@@ -218,5 +235,7 @@ impl_math_op!(SubAssign, sub_assign, Sub, sub);
 impl_math_op!(MulAssign, mul_assign, Mul, mul);
 impl_math_op!(DivAssign, div_assign, Div, div);
 
+/// A position in space.
 pub type Pos3 = Vec3;
+/// A linear RGB color, where `1.` is full intensity.
 pub type Color = Vec3;
@@ -0,0 +1,58 @@
+use path_tracing::{furnace, Camera, Color, DielectricMaterial, MaterialObject, Medium};
+
+const SAMPLES: usize = 256;
+
+fn assert_close(a: Color, b: Color, tolerance: f64) {
+    assert!(
+        (a - b).abs() < tolerance * b.abs(),
+        "{a:?} is not close to {b:?}"
+    );
+}
+
+/// Color seen through the middle of the unit sphere of `material` in a white
+/// furnace, along a diameter of it.
+fn through_the_middle(material: MaterialObject) -> Color {
+    let mut scene = furnace::scene(material, SAMPLES);
+    scene.camera.image_width = 1;
+    scene.camera.v_fov = 0.1;
+
+    let color = Camera::new(scene.camera).sample_pixel(&scene.world.into(), 0, 0, 0, SAMPLES);
+    color.sum / color.samples as f64
+}
+
+#[test]
+fn transmittance_follows_beer_lambert() {
+    let medium = Medium::new(1.5, Color::new(0., 0.5, 3.), 0);
+
+    for distance in [0_f64, 0.1, 1., 7.] {
+        let expected = Color::new(1., (-0.5 * distance).exp(), (-3. * distance).exp());
+        assert_close(medium.transmittance(distance), expected, 1e-12);
+    }
+}
+
+#[test]
+fn rays_are_absorbed_along_the_distance_travelled() {
+    // An index of 1 does not bend light, so rays cross the diameter.
+    let absorption = Color::new(0.5, 1., 2.);
+    let material = DielectricMaterial::new(1.).absorbing(absorption);
+    let expected = Color::new((-1_f64).exp(), (-2_f64).exp(), (-4_f64).exp());
+
+    assert_close(through_the_middle(material.into()), expected, 1e-3);
+}
+
+#[test]
+fn tinted_dielectrics_reach_their_color_at_their_distance() {
+    for (color, distance) in [
+        (Color::new(0.9, 0.5, 0.1), 1.),
+        (Color::new(0.2, 0.7, 1.), 2.),
+        (Color::new(1., 1., 1.), 0.3),
+    ] {
+        let material = DielectricMaterial::new(1.5).tinted(color, distance);
+        assert_close(material.medium().transmittance(distance), color, 1e-12);
+    }
+
+    // The diameter of the unit sphere.
+    let color = Color::new(0.9, 0.5, 0.1);
+    let material = DielectricMaterial::new(1.).tinted(color, 2.);
+    assert_close(through_the_middle(material.into()), color, 1e-3);
+}
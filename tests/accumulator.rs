@@ -0,0 +1,59 @@
+use std::io::ErrorKind;
+
+use path_tracing::{Accumulator, Color};
+
+fn accumulator(seed: Option<u64>) -> Accumulator {
+    let mut accumulator = Accumulator::new(3, 2).with_seed(seed);
+
+    for (i, pixel) in accumulator.pixels_mut().iter_mut().enumerate() {
+        pixel.add_sample(Color::new(i as f64, 0.5, 1.));
+    }
+
+    accumulator
+}
+
+fn header(width: u64, height: u64) -> Vec<u8> {
+    [
+        &b"PTACCUM3"[..],
+        &width.to_le_bytes(),
+        &height.to_le_bytes(),
+        &0_u64.to_le_bytes(),
+        &0_u64.to_le_bytes(),
+    ]
+    .concat()
+}
+
+#[test]
+fn checkpoints_keep_samples_and_seed() {
+    for seed in [None, Some(0), Some(42)] {
+        let accumulator = accumulator(seed);
+        let mut bytes = Vec::new();
+        accumulator.write_to(&mut bytes).unwrap();
+
+        assert_eq!(Accumulator::read_from(&bytes[..]).unwrap(), accumulator);
+    }
+}
+
+#[test]
+fn huge_checkpoints_are_invalid() {
+    for (width, height) in [(u64::MAX, 2), (1 << 20, 1 << 20)] {
+        let error = Accumulator::read_from(&header(width, height)[..]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData, "{width}x{height}");
+    }
+}
+
+#[test]
+fn truncated_checkpoints_fail() {
+    let error = Accumulator::read_from(&header(1 << 14, 1 << 14)[..]).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn merging_the_same_seed_fails() {
+    let mut merged = accumulator(Some(1));
+    assert!(merged.merge(&accumulator(Some(1))).is_err());
+
+    merged.merge(&accumulator(Some(2))).unwrap();
+    assert_eq!(merged.seed(), None);
+    assert_eq!(merged.min_samples(), 2);
+}
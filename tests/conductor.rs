@@ -0,0 +1,67 @@
+use path_tracing::{furnace, Color, ConductorMaterial};
+
+/// Reflectance at normal incidence of a conductor of complex index `eta + i
+/// k` in vacuum.
+fn normal_reflectance(eta: f64, k: f64) -> f64 {
+    ((eta - 1.).powi(2) + k * k) / ((eta + 1.).powi(2) + k * k)
+}
+
+#[test]
+fn gold_reflects_as_measured_at_normal_incidence() {
+    let gold = ConductorMaterial::gold(0.);
+    let reflectance = gold.fresnel(1.);
+
+    for (channel, eta, k) in [
+        (reflectance.x(), gold.eta().x(), gold.k().x()),
+        (reflectance.y(), gold.eta().y(), gold.k().y()),
+        (reflectance.z(), gold.eta().z(), gold.k().z()),
+    ] {
+        assert!(
+            (channel - normal_reflectance(eta, k)).abs() < 1e-12,
+            "{channel} for {eta} + {k}i"
+        );
+    }
+
+    // The reflectance of gold usually given for real-time rendering, in
+    // linear RGB.
+    let expected = Color::new(1., 0.766, 0.336);
+    let difference = reflectance - expected;
+    assert!(
+        [difference.x(), difference.y(), difference.z()]
+            .iter()
+            .all(|d| d.abs() < 0.05),
+        "{reflectance:?} != {expected:?}"
+    );
+}
+
+#[test]
+fn conductors_reflect_everything_at_grazing_angles() {
+    for metal in [
+        ConductorMaterial::gold(0.),
+        ConductorMaterial::silver(0.),
+        ConductorMaterial::copper(0.),
+        ConductorMaterial::aluminium(0.),
+    ] {
+        let grazing = metal.fresnel(0.);
+        assert!(
+            (grazing - Color::new(1., 1., 1.)).abs() < 1e-9,
+            "{metal:?}: {grazing:?}"
+        );
+    }
+}
+
+#[test]
+fn white_conductors_lose_energy_with_roughness() {
+    let mut previous = furnace::measure(furnace::white_conductor(0.).into(), 16);
+    assert!(previous.loss().abs() < 1e-3, "{previous}");
+
+    // Only the light which bounces between microfacets is lost, which is
+    // more of it on rougher surfaces.
+    for roughness in [0.1, 0.3, 0.6] {
+        let result = furnace::measure(furnace::white_conductor(roughness).into(), 16);
+
+        assert!(result.loss() > previous.loss(), "{result} after {previous}");
+        assert!(result.loss() < 0.5, "{result}");
+        previous = result;
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::OnceLock;
+
+use path_tracing::{denoise::denoise, Aovs, Camera, DenoiseOptions, FloatImage, Scene};
+
+const SEED: u64 = 7;
+
+fn render(samples: usize, seed: u64) -> (FloatImage, Aovs) {
+    let mut scene = Scene::from_name("three-spheres").unwrap();
+    scene.camera.image_width = 96;
+    scene.camera.samples_per_pixel = samples;
+    scene.camera.seed = Some(seed);
+
+    let (accumulator, aovs, _) = Camera::new(scene.camera).render_with_aovs(&scene.world.into());
+
+    (accumulator.to_float_image(), aovs)
+}
+
+/// A render with enough samples to be nearly free of noise, shared by the
+/// tests.
+fn reference() -> &'static FloatImage {
+    static REFERENCE: OnceLock<FloatImage> = OnceLock::new();
+    REFERENCE.get_or_init(|| render(1024, SEED + 1).0)
+}
+
+/// Mean squared error of the displayed values, which are clamped to one.
+fn mse(a: &FloatImage, b: &FloatImage) -> f64 {
+    let sum: f64 = a
+        .data()
+        .iter()
+        .zip(b.data())
+        .map(|(a, b)| (a.min(1.) - b.min(1.)).powi(2))
+        .sum();
+
+    sum / a.data().len() as f64
+}
+
+#[test]
+fn denoising_reduces_the_error_against_a_reference() {
+    let (noisy, aovs) = render(16, SEED);
+
+    let denoised = denoise(&noisy, &aovs, &DenoiseOptions::default());
+    let (noisy_error, denoised_error) = (mse(&noisy, reference()), mse(&denoised, reference()));
+
+    assert!(denoised_error < noisy_error / 2.);
+}
+
+#[test]
+fn more_iterations_remove_more_noise() {
+    let (noisy, aovs) = render(16, SEED);
+
+    let error = |iterations| {
+        let options = DenoiseOptions {
+            iterations,
+            ..DenoiseOptions::default()
+        };
+
+        mse(&denoise(&noisy, &aovs, &options), reference())
+    };
+
+    assert!(error(3) < error(1));
+}
+
+#[test]
+fn zero_strength_keeps_the_image() {
+    let (noisy, aovs) = render(4, SEED);
+    let options = DenoiseOptions {
+        strength: 0.,
+        ..DenoiseOptions::default()
+    };
+
+    assert_eq!(denoise(&noisy, &aovs, &options), noisy);
+}
+
+#[test]
+fn passes_wider_than_the_image_change_nothing() {
+    let (noisy, aovs) = render(4, SEED);
+    let denoised = |iterations| {
+        let options = DenoiseOptions {
+            iterations,
+            ..DenoiseOptions::default()
+        };
+
+        denoise(&noisy, &aovs, &options)
+    };
+
+    // The image is 96 pixels wide, which the seventh pass spans.
+    let widest = denoised(7);
+    assert_eq!(denoised(8), widest);
+    assert_eq!(denoised(usize::MAX), widest);
+}
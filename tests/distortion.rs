@@ -0,0 +1,99 @@
+use path_tracing::{Camera, Distortion, Intrinsics, Scene};
+
+const BROWN_CONRADY: Distortion = Distortion::BrownConrady {
+    k1: -0.25,
+    k2: 0.05,
+    k3: 0.01,
+    p1: 0.002,
+    p2: -0.001,
+};
+const DIVISION: Distortion = Distortion::Division { k1: -0.2, k2: 0.02 };
+
+/// Points of the normalized plane within the field of view of common lenses.
+fn grid() -> impl Iterator<Item = (f64, f64)> {
+    (-8..=8).flat_map(|i| (-6..=6).map(move |j| (i as f64 / 10., j as f64 / 10.)))
+}
+
+fn assert_close(a: (f64, f64), b: (f64, f64)) {
+    assert!(
+        (a.0 - b.0).hypot(a.1 - b.1) < 1e-9,
+        "{a:?} is not close to {b:?}"
+    );
+}
+
+#[test]
+fn distortions_invert() {
+    for distortion in [BROWN_CONRADY, DIVISION] {
+        for p in grid() {
+            let distorted = distortion.distort(p).unwrap();
+            assert_close(distortion.undistort(distorted).unwrap(), p);
+
+            let undistorted = distortion.undistort(p).unwrap();
+            assert_close(distortion.distort(undistorted).unwrap(), p);
+        }
+    }
+}
+
+#[test]
+fn points_out_of_the_model_have_no_inverse() {
+    // The division model sends a radius of 1 / √-k1 to infinity.
+    let barrel = Distortion::Division { k1: -1., k2: 0. };
+    assert_eq!(barrel.undistort((1., 0.)), None);
+
+    // With a positive `k1`, undistorted radii are at most 0.5, so the
+    // inversion finds nothing further out.
+    let pincushion = Distortion::Division { k1: 1., k2: 0. };
+    assert!(pincushion.distort((0.4, 0.)).is_some());
+    assert_eq!(pincushion.distort((0.6, 0.)), None);
+}
+
+#[test]
+fn intrinsics_from_the_field_of_view_render_the_same_image() {
+    let mut scene = Scene::from_name("three-spheres").unwrap();
+    scene.camera.image_width = 64;
+    scene.camera.samples_per_pixel = 4;
+    scene.camera.seed = Some(1);
+
+    let world = scene.world.into();
+    let by_field_of_view = Camera::new(scene.camera.clone()).render_parallel(&world);
+
+    let height = (400. / scene.camera.aspect_ratio) as usize;
+    scene.camera.intrinsics = Some(Intrinsics::from_v_fov(scene.camera.v_fov, 400, height));
+    let by_intrinsics = Camera::new(scene.camera).render_parallel(&world);
+
+    assert_eq!(
+        (by_intrinsics.width(), by_intrinsics.height()),
+        (by_field_of_view.width(), by_field_of_view.height())
+    );
+    assert!(by_intrinsics.pixels() == by_field_of_view.pixels());
+}
+
+#[test]
+fn scaling_keeps_pixel_edges_aligned() {
+    let intrinsics = Intrinsics {
+        fx: 1200.,
+        fy: 1180.,
+        cx: 950.3,
+        cy: 541.7,
+        width: 1920,
+        height: 1080,
+    };
+
+    for (width, height) in [(640, 360), (1921, 1081), (3840, 2160)] {
+        let scaled = intrinsics.scaled(width, height);
+
+        // The outer edges of the corner pixels see the same directions.
+        for (corner, scaled_corner) in [
+            ((-0.5, -0.5), (-0.5, -0.5)),
+            (
+                (1920. - 0.5, 1080. - 0.5),
+                (width as f64 - 0.5, height as f64 - 0.5),
+            ),
+        ] {
+            assert_close(
+                scaled.normalize(scaled_corner),
+                intrinsics.normalize(corner),
+            );
+        }
+    }
+}
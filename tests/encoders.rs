@@ -0,0 +1,149 @@
+use std::{io::Cursor, time::Duration};
+
+use path_tracing::{
+    apng::write_apng,
+    gif::write_gif,
+    quantize::{Dither, Palette, Quantizer, MAX_COLORS},
+    GifOptions, PPMColor, PPMImage,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// An image of random pixels taken from `colors`.
+fn noise(rng: &mut StdRng, width: usize, height: usize, colors: &[PPMColor]) -> PPMImage {
+    let pixels = (0..width * height)
+        .map(|_| colors[rng.gen_range(0..colors.len())])
+        .collect();
+
+    PPMImage::new_with(width, height, pixels)
+}
+
+fn random_colors(rng: &mut StdRng, count: usize) -> Vec<PPMColor> {
+    (0..count)
+        .map(|_| PPMColor::new(rng.gen(), rng.gen(), rng.gen()))
+        .collect()
+}
+
+#[test]
+fn gif_decodes_to_the_palette_colors() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let colors = random_colors(&mut rng, 100);
+
+    // Noise uses every color of the palette in every frame.
+    let frames: Vec<_> = (0..3).map(|_| noise(&mut rng, 160, 120, &colors)).collect();
+
+    let options = GifOptions::default();
+    let mut bytes = Vec::new();
+    write_gif(&frames, &options, &mut bytes).unwrap();
+
+    let mut decode_options = gif::DecodeOptions::new();
+    decode_options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = decode_options.read_info(Cursor::new(bytes)).unwrap();
+
+    assert_eq!((decoder.width(), decoder.height()), (160, 120));
+
+    let palette = Palette::new(&frames, options.colors, options.quantizer);
+    let mut decoded = 0;
+
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        let expected: Vec<u8> = palette
+            .indices(&frames[decoded], Dither::None)
+            .into_iter()
+            .flat_map(|i| {
+                let color = palette.colors()[usize::from(i)];
+                [color.r(), color.g(), color.b(), 255]
+            })
+            .collect();
+
+        assert!(frame.buffer[..] == expected[..], "frame {decoded} differs");
+        decoded += 1;
+    }
+
+    assert_eq!(decoded, frames.len());
+}
+
+#[test]
+fn gif_keeps_few_colors_exactly() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let colors = random_colors(&mut rng, 16);
+    let frame = noise(&mut rng, 64, 64, &colors);
+
+    let mut bytes = Vec::new();
+    write_gif(
+        std::slice::from_ref(&frame),
+        &GifOptions::default(),
+        &mut bytes,
+    )
+    .unwrap();
+
+    let mut decode_options = gif::DecodeOptions::new();
+    decode_options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = decode_options.read_info(Cursor::new(bytes)).unwrap();
+    let decoded = decoder.read_next_frame().unwrap().unwrap();
+
+    let expected: Vec<u8> = frame
+        .pixels()
+        .iter()
+        .flat_map(|color| [color.r(), color.g(), color.b(), 255])
+        .collect();
+
+    assert!(decoded.buffer[..] == expected[..]);
+}
+
+#[test]
+fn palettes_have_at_most_256_colors() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let colors = random_colors(&mut rng, 5000);
+    let frame = noise(&mut rng, 100, 100, &colors);
+
+    for quantizer in [Quantizer::MedianCut, Quantizer::Octree] {
+        for max_colors in [1, 2, 16, 256, 1000] {
+            let palette = Palette::new(std::slice::from_ref(&frame), max_colors, quantizer);
+            let len = palette.colors().len();
+
+            assert!(
+                (1..=max_colors.min(MAX_COLORS)).contains(&len),
+                "{quantizer:?} {max_colors}: {len} colors"
+            );
+        }
+    }
+}
+
+#[test]
+fn apng_decodes_to_the_same_pixels() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let colors = random_colors(&mut rng, 300);
+
+    // Noise barely compresses, while a flat image is made of long matches.
+    let flat = PPMImage::new_with(70, 50, vec![PPMColor::new(10, 200, 30); 70 * 50]);
+    let frames = [noise(&mut rng, 70, 50, &colors), flat];
+
+    let mut bytes = Vec::new();
+    write_apng(&frames, Duration::from_millis(40), &mut bytes).unwrap();
+
+    // Makes the decoder check the CRC of every chunk and the Adler-32 of
+    // every zlib stream, which it skips by default.
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.ignore_checksums(false);
+    let mut reader = decoder.read_info().unwrap();
+    let animation = reader.info().animation_control.unwrap();
+    assert_eq!(animation.num_frames, 2);
+
+    let mut buffer = vec![0; reader.output_buffer_size()];
+
+    for (i, frame) in frames.iter().enumerate() {
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!((info.width, info.height), (70, 50));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+
+        let expected: Vec<u8> = frame
+            .pixels()
+            .iter()
+            .flat_map(|color| [color.r(), color.g(), color.b()])
+            .collect();
+
+        assert!(
+            buffer[..info.buffer_size()] == expected[..],
+            "frame {i} differs"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use path_tracing::{
+    fish::{self, FishError, RandomLog},
+    Camera, Color, ConductorMaterial, PPMImage, Pos3, Scene, Sphere,
+};
+
+/// A tiny render of the three spheres by the fish renderer, with the random
+/// numbers it drew for each pixel. To record both again, from the root of the
+/// repository:
+///
+/// ```fish
+/// rm -f tests/fish/random.log
+/// PATH_TRACE_RANDOM_LOG=tests/fish/random.log fish fish/path-trace.fish scene (string split -n ' ' < tests/fish/scene.txt) > tests/fish/expected.ppm
+/// ```
+const SCENE: &str = include_str!("fish/scene.txt");
+const RANDOM_LOG: &str = include_str!("fish/random.log");
+const EXPECTED: &str = include_str!("fish/expected.ppm");
+
+#[test]
+fn scenes_survive_encoding() {
+    let scene = Scene::from_name("three-spheres").unwrap();
+    let encoded = fish::encode_scene(&scene).unwrap();
+    let decoded = fish::decode_scene(&encoded).unwrap();
+
+    assert_eq!(decoded.camera.image_width, scene.camera.image_width);
+    assert_eq!(decoded.camera.look_from, scene.camera.look_from);
+    assert_eq!(decoded.world.objects().len(), scene.world.objects().len());
+    assert_eq!(fish::encode_scene(&decoded).unwrap(), encoded);
+
+    let scene = fish::decode_scene(SCENE).unwrap();
+    assert_eq!(fish::encode_scene(&scene).unwrap(), SCENE);
+}
+
+#[test]
+fn unsupported_scenes_are_not_encoded() {
+    let mut scene = fish::decode_scene(SCENE).unwrap();
+    let gold = ConductorMaterial::new(
+        Color::new(0.18, 0.42, 1.37),
+        Color::new(3.42, 2.35, 1.77),
+        0.,
+    );
+    scene
+        .world
+        .add(Sphere::new(Pos3::new(0., 1., -1.), 0.5, gold.into()).into());
+
+    assert_eq!(
+        fish::encode_scene(&scene),
+        Err(FishError::Unsupported("conductors"))
+    );
+}
+
+#[test]
+fn replays_the_recorded_numbers() {
+    let scene = fish::decode_scene(SCENE).unwrap();
+    let log: RandomLog = RANDOM_LOG.parse().unwrap();
+    let expected: PPMImage = EXPECTED.parse().unwrap();
+
+    let camera = Camera::new(scene.camera);
+    let render = fish::render_recorded(&camera, &scene.world.into(), &log);
+
+    // Rays are timed without drawing from the log, as the fish renderer has
+    // no time.
+    assert_eq!(render.desynced, []);
+
+    let report = fish::compare(&render.image, &expected, fish::DEFAULT_TOLERANCE).unwrap();
+    assert!(report.is_match(), "{report}");
+}
+
+#[test]
+fn reports_pixels_which_diverge() {
+    let scene = fish::decode_scene(SCENE).unwrap();
+    // An extra number at the end of the first pixel.
+    let log: RandomLog = RANDOM_LOG
+        .replacen("pixel 1 0", "0.5\npixel 1 0", 1)
+        .parse()
+        .unwrap();
+
+    let camera = Camera::new(scene.camera);
+    let render = fish::render_recorded(&camera, &scene.world.into(), &log);
+
+    assert_eq!(render.desynced, [(0, 0)]);
+}
@@ -0,0 +1,39 @@
+use path_tracing::{furnace, Color, DielectricMaterial, LambertianMaterial, MetalMaterial};
+
+const SAMPLES: usize = 16;
+const TOLERANCE: f64 = 1e-3;
+
+#[test]
+fn white_lambertian_vanishes() {
+    let result = furnace::measure(
+        LambertianMaterial::new(Color::new(1., 1., 1.)).into(),
+        SAMPLES,
+    );
+    assert!(result.loss().abs() < TOLERANCE, "{result}");
+}
+
+#[test]
+fn dielectrics_vanish() {
+    for index in [1., 1. / 1.5, 1.33, 1.5, 2.4] {
+        let result = furnace::measure(DielectricMaterial::new(index).into(), SAMPLES);
+        assert!(result.loss().abs() < TOLERANCE, "{result}");
+    }
+}
+
+#[test]
+fn smooth_white_metal_vanishes() {
+    let result = furnace::measure(
+        MetalMaterial::new(Color::new(1., 1., 1.), 0.).into(),
+        SAMPLES,
+    );
+    assert!(result.loss().abs() < TOLERANCE, "{result}");
+}
+
+#[test]
+fn grey_lambertian_loses_energy() {
+    let result = furnace::measure(
+        LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into(),
+        SAMPLES,
+    );
+    assert!((result.energy - 0.5).abs() < 0.01, "{result}");
+}
@@ -0,0 +1,104 @@
+//! Renders small versions of the built-in scenes and compares them against
+//! the references in `tests/golden`, which were rendered with many more
+//! samples. The renders are noisy, so they only have to be statistically
+//! close to the references: a change of material or sampling moves them much
+//! further than the noise does.
+//!
+//! Run with `UPDATE_GOLDEN=1` to render the references again after an
+//! intended change.
+
+use std::{env, fs, path::PathBuf};
+
+use path_tracing::{metrics, Camera, FloatImage, PPMImage, Scene};
+
+const WIDTH: usize = 64;
+const SAMPLES: usize = 64;
+const REFERENCE_SAMPLES: usize = 4096;
+const SEED: u64 = 1;
+
+/// Lowest PSNR in decibels, and SSIM, a render may have against its
+/// reference. The noise of a render stays well above them.
+const MIN_PSNR: f64 = 30.;
+const MIN_SSIM: f64 = 0.9;
+/// Largest relative difference between the mean values of a render and its
+/// reference. Noise averages out over the image, so this is the most
+/// sensitive check: it stays below 0.3% across seeds, while making metals 5%
+/// darker moves it by 0.7%.
+const MAX_BIAS: f64 = 0.005;
+
+fn render(name: &str, samples: usize, seed: u64) -> PPMImage {
+    let mut scene = Scene::from_name(name).unwrap();
+    scene.camera.image_width = WIDTH;
+    scene.camera.samples_per_pixel = samples;
+    scene.camera.seed = Some(seed);
+
+    Camera::new(scene.camera).render_parallel(&scene.world.into())
+}
+
+/// The mean of every value of the image.
+fn mean(image: &FloatImage) -> f64 {
+    image.data().iter().sum::<f64>() / image.data().len() as f64
+}
+
+fn check(name: &str) {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let path = directory.join(format!("{name}.ppm"));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(&directory).unwrap();
+        render(name, REFERENCE_SAMPLES, SEED + 1)
+            .save(&path)
+            .unwrap();
+    }
+
+    let reference: PPMImage = fs::read_to_string(&path)
+        .expect("missing reference, run with UPDATE_GOLDEN=1")
+        .parse()
+        .unwrap();
+    let reference = FloatImage::from(&reference);
+    let image = FloatImage::from(&render(name, SAMPLES, SEED));
+    let comparison = metrics::Comparison::new(&image, &reference);
+    let bias = mean(&image) / mean(&reference) - 1.;
+
+    if comparison.psnr < MIN_PSNR || comparison.ssim < MIN_SSIM || bias.abs() > MAX_BIAS {
+        let heatmap =
+            PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.heatmap.ppm"));
+        metrics::heatmap(&metrics::relative_error(&image, &reference), 1.)
+            .save(&heatmap)
+            .unwrap();
+
+        panic!(
+            "{name} differs from its reference: {comparison}, bias {:.2}%, see {}",
+            100. * bias,
+            heatmap.display()
+        );
+    }
+}
+
+#[test]
+fn book_cover() {
+    check("book-cover");
+}
+
+#[test]
+fn three_spheres() {
+    check("three-spheres");
+}
+
+#[test]
+fn identical_images_match_exactly() {
+    let image = FloatImage::from(&render("three-spheres", 4, SEED));
+    let comparison = metrics::Comparison::new(&image, &image);
+
+    assert_eq!(comparison.mse, 0.);
+    assert_eq!(comparison.psnr, f64::INFINITY);
+    assert!((comparison.ssim - 1.).abs() < 1e-9);
+    assert_eq!(comparison.mean_relative_error, 0.);
+}
+
+#[test]
+#[should_panic(expected = "the images are empty")]
+fn empty_images_cannot_be_compared() {
+    let empty = FloatImage::new(0, 0, 3);
+    metrics::ssim(&empty, &empty);
+}
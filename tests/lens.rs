@@ -0,0 +1,126 @@
+use path_tracing::{
+    Background, Camera, CameraOptions, Color, HittableList, Lens, LensSystem, RealisticLens,
+};
+
+/// A paraxial ray traced through `system` from the front, as its height
+/// and the product of its slope and the index it travels in, which stay
+/// linear in each other near the axis. Returns the ray leaving the rear
+/// surface.
+fn paraxial(system: &LensSystem, mut height: f64, mut slope: f64) -> (f64, f64) {
+    let elements = system.elements();
+    let mut index = 1.;
+
+    for (i, element) in elements.iter().enumerate() {
+        if !element.is_stop() {
+            slope -= height * (element.ior - index) / element.radius;
+        }
+
+        index = element.ior;
+
+        if i + 1 < elements.len() {
+            height += element.thickness * slope / index;
+        }
+    }
+
+    (height, slope)
+}
+
+/// Distance from the rear surface to where a ray parallel to the axis
+/// crosses it.
+fn back_focal_distance(system: &LensSystem) -> f64 {
+    let (height, slope) = paraxial(system, 1., 0.);
+    -height / slope
+}
+
+/// Length of the lens from the front surface to the rear one.
+fn length(system: &LensSystem) -> f64 {
+    let elements = system.elements();
+    elements[..elements.len() - 1]
+        .iter()
+        .map(|element| element.thickness)
+        .sum()
+}
+
+#[test]
+fn focal_lengths_match_the_names() {
+    for (system, focal_length) in [
+        (LensSystem::double_gauss_100mm(), 100.),
+        (LensSystem::double_gauss_50mm(), 50.),
+    ] {
+        let measured = system.focal_length().unwrap();
+        assert!(
+            (measured - focal_length).abs() < 0.01 * focal_length,
+            "{measured} mm"
+        );
+
+        // A ray entering at a height of one leaves with a slope of one over
+        // the focal length.
+        let (_, slope) = paraxial(&system, 1., 0.);
+        assert!((measured + 1. / slope).abs() < 1e-3, "{measured} mm");
+    }
+}
+
+#[test]
+fn focus_at_infinity_is_the_back_focal_distance() {
+    for system in [
+        LensSystem::double_gauss_100mm(),
+        LensSystem::double_gauss_50mm(),
+    ] {
+        let film_distance = system.film_distance(f64::INFINITY).unwrap();
+        let expected = back_focal_distance(&system);
+
+        assert!(
+            (film_distance - expected).abs() < 1e-3,
+            "{film_distance} mm, not {expected} mm"
+        );
+    }
+}
+
+#[test]
+fn focusing_images_points_on_the_film() {
+    let system = LensSystem::double_gauss_50mm();
+    let at_infinity = system.film_distance(f64::INFINITY).unwrap();
+
+    for distance in [300., 1000., 5000.] {
+        let film_distance = system.film_distance(distance).unwrap();
+
+        // The lens moves away from the film to focus closer.
+        assert!(film_distance > at_infinity);
+
+        // A ray from the point on the axis at `distance` from the film
+        // crosses the axis again on the film.
+        let object = distance - film_distance - length(&system);
+        let (height, slope) = paraxial(&system, object * 1e-3, 1e-3);
+        let image = -height / slope;
+
+        assert!(
+            (image - film_distance).abs() < 1e-3 * film_distance,
+            "{distance} mm: the image is {image} mm behind the lens, not {film_distance} mm"
+        );
+    }
+
+    // Closer than the front of the lens.
+    assert_eq!(system.film_distance(10.), None);
+}
+
+#[test]
+fn weights_average_to_one_at_the_center() {
+    let camera = Camera::new(CameraOptions {
+        aspect_ratio: 1.,
+        image_width: 101,
+        seed: Some(0),
+        lens: Lens::Realistic(RealisticLens::new(LensSystem::double_gauss_50mm())),
+        background: Background::Uniform(Color::new(1., 1., 1.)),
+        ..CameraOptions::default()
+    });
+    let world = HittableList::default().into();
+
+    let center = camera.sample_pixel(&world, 50, 50, 0, 4096);
+    let center = center.sum.x() / center.samples as f64;
+    assert!((center - 1.).abs() < 0.02, "{center}");
+
+    // Real lenses darken the corners.
+    let corner = camera.sample_pixel(&world, 0, 0, 0, 4096);
+    let corner = corner.sum.x() / corner.samples as f64;
+    assert!(corner < 0.5 * center, "{corner}");
+}
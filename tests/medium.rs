@@ -0,0 +1,128 @@
+use path_tracing::{
+    Color, DielectricMaterial, HitRecord, Material, Medium, MediumStack, Pos3, Ray, Vec3,
+};
+
+const CLEAR: Color = Color::new(0., 0., 0.);
+
+/// Whether `medium` is the one of index `index`, which the stack keeps in
+/// single precision.
+fn is(medium: Medium, index: f64) -> bool {
+    (medium.refraction_index() - index).abs() < 1e-6
+}
+
+/// The surface of `object_id`, at the origin with an outward normal along
+/// `+z`, hit by a ray in `media` going in `direction`.
+fn hit(
+    material: DielectricMaterial,
+    object_id: usize,
+    media: MediumStack,
+    direction: Vec3,
+) -> (Ray, HitRecord) {
+    let ray = Ray::new(Pos3::default() - direction, direction).in_media(media);
+    let mut rec = HitRecord::new(
+        1.,
+        Pos3::default(),
+        material.into(),
+        &ray,
+        Vec3::new(0., 0., 1.),
+    );
+    rec.object_id = object_id;
+
+    (ray, rec)
+}
+
+/// Ratio of the sines of the angles to the normal of `direction` before and
+/// after refracting through the surface of [`hit`], which is the relative
+/// index of refraction.
+fn relative_index(material: DielectricMaterial, object_id: usize, media: MediumStack) -> f64 {
+    let incoming = Vec3::new(0.3, 0., -1.).normalize();
+    let incoming = if media.contains(object_id) {
+        -incoming
+    } else {
+        incoming
+    };
+    let normal = Vec3::new(0., 0., 1.);
+    let (ray, rec) = hit(material, object_id, media, incoming);
+
+    // Most rays refract, which only goes through the surface.
+    let refracted = (0..100)
+        .map(|_| *material.scatter(&ray, &rec).unwrap().scattered.direction())
+        .find(|direction| direction.z() * incoming.z() > 0.)
+        .unwrap()
+        .normalize();
+
+    incoming.cross(&normal).abs() / refracted.cross(&normal).abs()
+}
+
+#[test]
+fn ice_in_water_bends_by_their_ratio() {
+    let water = DielectricMaterial::new(1.33).with_priority(1);
+    let ice = DielectricMaterial::new(1.31).with_priority(2);
+    let in_water = MediumStack::EMPTY.entered(1, water.medium());
+
+    let ratio = relative_index(ice, 2, in_water);
+    assert!((ratio - 1.31 / 1.33).abs() < 1e-6, "{ratio}");
+}
+
+#[test]
+fn water_in_glass_bends_by_their_ratio() {
+    let glass = DielectricMaterial::new(1.5).with_priority(1);
+    let water = DielectricMaterial::new(1.33).with_priority(2);
+    let in_water = MediumStack::EMPTY
+        .entered(1, glass.medium())
+        .entered(2, water.medium());
+
+    // Leaving the water, into the glass around it.
+    let ratio = relative_index(water, 2, in_water);
+    assert!((ratio - 1.5 / 1.33).abs() < 1e-6, "{ratio}");
+}
+
+#[test]
+fn surfaces_inside_higher_priorities_are_skipped() {
+    let glass = DielectricMaterial::new(1.5).with_priority(1);
+    let water = DielectricMaterial::new(1.33).with_priority(2);
+    let in_water = MediumStack::EMPTY.entered(2, water.medium());
+
+    let direction = Vec3::new(0.3, 0.2, -1.).normalize();
+    let (ray, rec) = hit(glass, 1, in_water, direction);
+
+    for _ in 0..100 {
+        let scattered = glass.scatter(&ray, &rec).unwrap().scattered;
+
+        assert_eq!(*scattered.direction(), direction);
+        assert!(scattered.media().contains(1));
+        assert!(is(scattered.medium(), 1.33));
+    }
+}
+
+#[test]
+fn highest_priority_fills_overlaps() {
+    let low = Medium::new(1.5, CLEAR, 1);
+    let high = Medium::new(1.33, Color::new(0.1, 0.2, 0.3), 2);
+    let equal = Medium::new(1.2, CLEAR, 2);
+
+    let media = MediumStack::EMPTY.entered(1, high).entered(2, low);
+    assert!(is(media.current(), 1.33));
+    assert!(is(media.around(1), 1.5));
+    assert!(is(media.around(2), 1.33));
+    assert_eq!(media.left(1).left(2), MediumStack::EMPTY);
+
+    // The last one entered among equal priorities.
+    assert!(is(media.entered(3, equal).current(), 1.2));
+}
+
+#[test]
+fn objects_past_the_nesting_limit_are_ignored() {
+    let media = (1..=5).fold(MediumStack::EMPTY, |media, id| {
+        media.entered(id, Medium::new(1. + id as f64 / 10., CLEAR, 0))
+    });
+
+    assert!((1..=4).all(|id| media.contains(id)));
+    assert!(!media.contains(5));
+    assert!(is(media.current(), 1.4));
+
+    // Leaving one makes room for another.
+    let media = media.left(2).entered(5, Medium::new(1.5, CLEAR, 0));
+    assert!(media.contains(5));
+    assert!(is(media.current(), 1.5));
+}
@@ -0,0 +1,138 @@
+use std::f64::consts::PI;
+
+use path_tracing::{microfacet::Ggx, Vec3};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const SAMPLES: usize = 400_000;
+
+/// A direction at `theta` from the normal, above the surface.
+fn direction(theta: f64, phi: f64) -> Vec3 {
+    Vec3::new(
+        theta.sin() * phi.cos(),
+        theta.sin() * phi.sin(),
+        theta.cos(),
+    )
+}
+
+fn uniform_hemisphere(rng: &mut StdRng) -> Vec3 {
+    let z: f64 = rng.gen();
+    let phi = rng.gen_range(0.0..2. * PI);
+    let r = (1. - z * z).sqrt();
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+fn distributions() -> [Ggx; 4] {
+    [
+        Ggx::new(0.1, 0.1),
+        Ggx::new(0.4, 0.4),
+        Ggx::new(0.8, 0.8),
+        Ggx::new(0.2, 0.6),
+    ]
+}
+
+#[test]
+fn normals_cover_the_projected_area() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for ggx in distributions() {
+        let area = (0..SAMPLES)
+            .map(|_| {
+                let m = uniform_hemisphere(&mut rng);
+                ggx.d(m) * m.z() * 2. * PI
+            })
+            .sum::<f64>()
+            / SAMPLES as f64;
+
+        assert!((area - 1.).abs() < 0.02, "{ggx:?}: {area}");
+    }
+}
+
+#[test]
+fn visible_normal_pdf_integrates_to_one() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for ggx in distributions() {
+        for theta in [0., 0.7, 1.3] {
+            let w = direction(theta, 0.4);
+
+            let total = (0..SAMPLES)
+                .map(|_| ggx.visible_normal_pdf(w, uniform_hemisphere(&mut rng)) * 2. * PI)
+                .sum::<f64>()
+                / SAMPLES as f64;
+
+            assert!((total - 1.).abs() < 0.02, "{ggx:?} {theta}: {total}");
+        }
+    }
+}
+
+#[test]
+fn sampled_normals_are_visible() {
+    let mut rng = StdRng::seed_from_u64(2);
+
+    for ggx in distributions() {
+        for theta in [0., 0.7, 1.3, 1.55] {
+            let w = direction(theta, 2.);
+
+            for _ in 0..10_000 {
+                let m = ggx.sample_visible_normal(w, rng.gen(), rng.gen());
+
+                assert!((m.abs() - 1.).abs() < 1e-9, "{ggx:?} {theta}: {m:?}");
+                assert!(m.z() >= 0., "{ggx:?} {theta}: below the surface {m:?}");
+                assert!(w.dot(&m) >= -1e-9, "{ggx:?} {theta}: facing away {m:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn sampling_matches_the_pdf() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let ggx = Ggx::new(0.3, 0.5);
+    let w = direction(0.9, 0.5);
+
+    // The mean of any function over sampled normals is its integral against
+    // the density, here of the height of the normal.
+    let sampled = (0..SAMPLES)
+        .map(|_| ggx.sample_visible_normal(w, rng.gen(), rng.gen()).z())
+        .sum::<f64>()
+        / SAMPLES as f64;
+
+    let integrated = (0..SAMPLES)
+        .map(|_| {
+            let m = uniform_hemisphere(&mut rng);
+            m.z() * ggx.visible_normal_pdf(w, m) * 2. * PI
+        })
+        .sum::<f64>()
+        / SAMPLES as f64;
+
+    assert!(
+        (sampled - integrated).abs() < 0.01,
+        "sampled {sampled}, integrated {integrated}"
+    );
+}
+
+#[test]
+fn shadowing_weights_at_most_one() {
+    let mut rng = StdRng::seed_from_u64(4);
+
+    for ggx in distributions() {
+        for theta in [0., 0.7, 1.3, 1.55] {
+            let wo = direction(theta, 1.);
+
+            for _ in 0..10_000 {
+                let m = ggx.sample_visible_normal(wo, rng.gen(), rng.gen());
+                let wi = (-wo).reflect(&m);
+
+                if wi.z() <= 0. {
+                    continue;
+                }
+
+                let weight = ggx.g2(wo, wi) / ggx.g1(wo);
+                assert!(
+                    (0. ..=1.).contains(&weight),
+                    "{ggx:?} {theta}: weight {weight}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use path_tracing::{
+    Camera, CameraMotion, CameraOptions, Color, HittableList, Interpolation, LambertianMaterial,
+    Pos3, Sphere, Track,
+};
+
+#[test]
+fn depth_is_measured_from_the_camera_of_each_sample() {
+    // The near side of a large sphere, 10 in front of the camera at time 0.
+    let mut world = HittableList::default();
+    let material = LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)).into();
+    world.add(Sphere::new(Pos3::new(0., 0., -110.), 100., material).into());
+
+    // Backing away by 4 over the exposure.
+    let track = |z: f64| {
+        Track::new(
+            vec![(0., Pos3::new(0., 0., z)), (1., Pos3::new(0., 0., z + 4.))],
+            Interpolation::Linear,
+        )
+    };
+    let camera = Camera::new(CameraOptions {
+        aspect_ratio: 1.,
+        image_width: 1,
+        samples_per_pixel: 1024,
+        v_fov: 0.1,
+        seed: Some(0),
+        motion: CameraMotion {
+            look_from: Some(track(0.)),
+            look_at: Some(track(-1.)),
+            ..CameraMotion::default()
+        },
+        ..CameraOptions::default()
+    });
+
+    let (_, aovs, _) = camera.render_with_aovs(&world.into());
+
+    // On average, the camera is halfway.
+    let depth = aovs.depth.get(0, 0)[0];
+    assert!((depth - 12.).abs() < 0.2, "{depth}");
+}
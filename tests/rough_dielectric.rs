@@ -0,0 +1,193 @@
+use std::f64::consts::PI;
+
+use path_tracing::{
+    Color, HitRecord, Material, Medium, MediumStack, Pos3, Ray, RoughDielectricMaterial, Vec3,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const INDEX: f64 = 1.5;
+const SAMPLES: usize = 400_000;
+
+/// A direction at `theta` from the normal, above the surface or below it.
+fn direction(theta: f64, phi: f64, above: bool) -> Vec3 {
+    let z = if above { theta.cos() } else { -theta.cos() };
+    Vec3::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), z)
+}
+
+fn uniform_sphere(rng: &mut StdRng) -> Vec3 {
+    let z: f64 = rng.gen_range(-1.0..1.);
+    let phi = rng.gen_range(0.0..2. * PI);
+    let r = (1. - z * z).sqrt();
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+fn index_of_side(w: Vec3) -> f64 {
+    if w.z() > 0. {
+        1.
+    } else {
+        INDEX
+    }
+}
+
+#[test]
+fn reciprocal() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for roughness in [0.1, 0.3, 0.7] {
+        let material = RoughDielectricMaterial::new(INDEX, roughness);
+
+        for _ in 0..1000 {
+            let wo = uniform_sphere(&mut rng);
+            let wi = uniform_sphere(&mut rng);
+
+            // Radiance is not scaled on refraction, which makes the
+            // function reciprocal up to the squared index of the light.
+            let forward = material.eval(wo, wi) / index_of_side(wi).powi(2);
+            let backward = material.eval(wi, wo) / index_of_side(wo).powi(2);
+
+            assert!(
+                (forward - backward).abs() <= 1e-9 * forward.abs().max(1.),
+                "{roughness} {wo:?} {wi:?}: {forward} != {backward}"
+            );
+        }
+    }
+}
+
+#[test]
+fn sampling_matches_evaluation() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for roughness in [0.3, 0.6] {
+        let material = RoughDielectricMaterial::new(INDEX, roughness);
+
+        for above in [true, false] {
+            let wo = direction(0.6, 0.3, above);
+
+            let sampled = (0..SAMPLES)
+                .filter_map(|_| material.sample(wo, (rng.gen(), rng.gen(), rng.gen())))
+                .map(|(_, weight)| weight)
+                .sum::<f64>()
+                / SAMPLES as f64;
+
+            // The same energy, integrated over the sphere of directions.
+            let integrated = (0..SAMPLES)
+                .map(|_| {
+                    let wi = uniform_sphere(&mut rng);
+                    material.eval(wo, wi) * wi.z().abs() * 4. * PI
+                })
+                .sum::<f64>()
+                / SAMPLES as f64;
+
+            assert!(
+                (sampled - integrated).abs() < 0.03,
+                "{roughness} {above}: sampled {sampled}, integrated {integrated}"
+            );
+            assert!(
+                sampled <= 1.,
+                "{roughness} {above}: gained energy {sampled}"
+            );
+        }
+    }
+}
+
+#[test]
+fn conserves_energy() {
+    let mut rng = StdRng::seed_from_u64(2);
+
+    for roughness in [0., 0.05, 0.3, 0.7, 1.] {
+        let material = RoughDielectricMaterial::new(INDEX, roughness);
+
+        for theta in [0., 0.5, 1., 1.4] {
+            for above in [true, false] {
+                let wo = direction(theta, 0., above);
+
+                let energy = (0..SAMPLES / 10)
+                    .filter_map(|_| material.sample(wo, (rng.gen(), rng.gen(), rng.gen())))
+                    .map(|(_, weight)| weight)
+                    .sum::<f64>()
+                    / (SAMPLES / 10) as f64;
+
+                assert!(
+                    energy <= 1. + 1e-9,
+                    "{roughness} {theta} {above}: gained energy {energy}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn smooth_is_lossless() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let material = RoughDielectricMaterial::new(INDEX, 0.);
+
+    for theta in [0., 0.5, 1., 1.4] {
+        for above in [true, false] {
+            let wo = direction(theta, 0., above);
+
+            for _ in 0..1000 {
+                let (_, weight) = material
+                    .sample(wo, (rng.gen(), rng.gen(), rng.gen()))
+                    .expect("a smooth surface never shadows");
+                assert!((weight - 1.).abs() < 1e-6, "{theta} {above}: {weight}");
+            }
+        }
+    }
+}
+
+#[test]
+fn totally_reflects_inside() {
+    let mut rng = StdRng::seed_from_u64(4);
+
+    // Well beyond the critical angle of about 0.73 radians.
+    let wo = direction(1.1, 0.7, false);
+    let transmitted = direction(0.3, 0.7 + PI, true);
+
+    let smooth = RoughDielectricMaterial::new(INDEX, 0.);
+    assert_eq!(smooth.eval(wo, transmitted), 0.);
+
+    for _ in 0..10_000 {
+        let (wi, _) = smooth
+            .sample(wo, (rng.gen(), rng.gen(), rng.gen()))
+            .expect("a smooth surface never shadows");
+        assert!(
+            wi.z() < 0.,
+            "escaped through total internal reflection: {wi:?}"
+        );
+    }
+
+    // Only the few microfacets tilted past the critical angle let light out.
+    let rough = RoughDielectricMaterial::new(INDEX, 0.05);
+    let escaped = (0..10_000)
+        .filter_map(|_| rough.sample(wo, (rng.gen(), rng.gen(), rng.gen())))
+        .filter(|(wi, _)| wi.z() > 0.)
+        .count();
+    assert!(escaped < 100, "{escaped} escaped");
+}
+
+#[test]
+fn bends_by_the_indices_on_both_sides() {
+    // Rough glass in water of the same index, which light goes straight
+    // through.
+    let water = Medium::new(INDEX, Color::new(0., 0., 0.), 0);
+    let media = MediumStack::EMPTY.entered(1, water);
+    let glass = RoughDielectricMaterial::new(INDEX, 0.5);
+
+    let direction = Vec3::new(0.3, 0.2, -1.).normalize();
+    let ray = Ray::new(Pos3::new(0., 0., 1.), direction).in_media(media);
+    let mut rec = HitRecord::new(
+        1.,
+        Pos3::default(),
+        glass.into(),
+        &ray,
+        Vec3::new(0., 0., 1.),
+    );
+    rec.object_id = 2;
+
+    for _ in 0..1000 {
+        let scattered = glass.scatter(&ray, &rec).unwrap().scattered;
+
+        assert!((*scattered.direction() - direction).near_zero());
+        assert!(scattered.media().contains(2));
+    }
+}
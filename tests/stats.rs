@@ -0,0 +1,32 @@
+use path_tracing::{Camera, RenderStats, Scene};
+
+#[test]
+fn serial_and_parallel_renders_count_the_same_work() {
+    let mut scene = Scene::from_name("three-spheres").unwrap();
+    scene.camera.image_width = 32;
+    scene.camera.samples_per_pixel = 4;
+    scene.camera.seed = Some(1);
+
+    let camera = Camera::new(scene.camera);
+    let world = scene.world.into();
+    let (serial_image, serial) = camera.render_with_stats(&world);
+    let (parallel_image, parallel) = camera.render_parallel_with_stats(&world);
+
+    assert!(serial_image.pixels() == parallel_image.pixels());
+    assert_eq!(serial.samples, 32 * 18 * 4);
+    assert_eq!(
+        RenderStats {
+            elapsed: parallel.elapsed,
+            ..serial
+        },
+        parallel
+    );
+}
+
+#[test]
+fn path_lengths_are_left_out_without_paths() {
+    let stats = RenderStats::default().to_string();
+
+    assert!(stats.contains("paths: 0.0% escaped"), "{stats}");
+    assert!(!stats.contains("path lengths"), "{stats}");
+}
@@ -0,0 +1,27 @@
+use path_tracing::{Camera, PPMImage, Scene, TileOptions, TileOrder};
+
+fn render(tiles: TileOptions) -> PPMImage {
+    let mut scene = Scene::from_name("three-spheres").unwrap();
+    scene.camera.image_width = 50;
+    scene.camera.samples_per_pixel = 4;
+    scene.camera.seed = Some(3);
+    scene.camera.tiles = tiles;
+
+    Camera::new(scene.camera).render_parallel(&scene.world.into())
+}
+
+#[test]
+fn tiles_do_not_change_the_image() {
+    let reference = render(TileOptions::default());
+
+    for order in [TileOrder::Scanline, TileOrder::Spiral, TileOrder::Hilbert] {
+        // Sizes which divide the image, which do not, and which cover it.
+        for size in [1, 5, 7, 16, 64] {
+            let image = render(TileOptions { size, order });
+            assert!(
+                image.pixels() == reference.pixels(),
+                "{order:?} tiles of {size} differ"
+            );
+        }
+    }
+}